@@ -1,16 +1,14 @@
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::challenges::Challenge;
+use crate::randomizer::dependency::DependencyValue;
+use crate::randomizer::donations::Donation;
 use crate::randomizer::{targets, characters};
-
-pub enum Unlockable {
-    ItLives,
-    Polaroid,
-    Negative,
-    HolyMantle,
-}
+use strum::IntoEnumIterator;
 
 pub enum Unlock {
     Character(characters::Character),
     Target(targets::Target),
-    Unlockable(Unlockable),
+    Achievement(Achievement),
 }
 
 impl Unlock {
@@ -26,9 +24,14 @@ impl Unlock {
             return Some(Self::Target(targ));
         }
 
-        match Self::try_str_to_unlockable(lower_arg) {
-            Some(u) => Some(Self::Unlockable(u)),
-            None => None,
+        Self::try_str_to_achievement(lower_arg).map(Self::Achievement)
+    }
+
+    pub fn to_dependency_value(&self) -> DependencyValue {
+        match self {
+            Self::Character(ch) => DependencyValue::Character(*ch),
+            Self::Target(targ) => DependencyValue::Target(*targ),
+            Self::Achievement(achievement) => DependencyValue::Achievement(*achievement),
         }
     }
 
@@ -86,6 +89,8 @@ impl Unlock {
             "beast" => Some(Beast),
             "mother" => Some(Mother),
             "ultra_greed" => Some(UltraGreed),
+            "ultra_greedier" => Some(Greedier),
+            "home" => Some(Home),
             "hush" => Some(Hush),
             "satan" => Some(Satan),
             "isaac" if !require_disambiguation => Some(Isaac),
@@ -97,13 +102,38 @@ impl Unlock {
         }
     }
 
-    pub fn try_str_to_unlockable(str: &str) -> Option<Unlockable> {
-        match str {
-            "it_lives" => Some( Unlockable::ItLives ),
-            "polaroid" => Some( Unlockable::Polaroid ),
-            "negative" => Some( Unlockable::Negative ),
-            "holy_mantle" => Some( Unlockable::HolyMantle ),
-            _ => None
-        }
+    // an achievement by its name in snake case (a_secret_exit), the other achievements by their
+    // id (achievement_123)
+    pub fn try_str_to_achievement(str: &str) -> Option<Achievement> {
+        Achievement::iter().find(|achievement| {
+            achievement.to_string().to_lowercase().replace(' ', "_") == str
+        })
+    }
+}
+
+// a challenge by its number in the game's list or its name in snake case (its_in_the_cards)
+pub fn try_str_to_challenge(str: &str) -> Option<Challenge> {
+    if let Ok(number) = str.parse::<u32>() {
+        return Challenge::iter().find(|challenge| challenge.number() == number);
+    }
+
+    let lower_str = str.to_lowercase();
+    Challenge::iter().find(|challenge| {
+        let name: String = challenge
+            .to_string()
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == ' ')
+            .collect();
+
+        name.replace(' ', "_") == lower_str
+    })
+}
+
+pub fn try_str_to_donation(str: &str) -> Option<Donation> {
+    match str {
+        "donation_machine" => Some(Donation::DonationMachine),
+        "greed_donation_machine" => Some(Donation::GreedDonationMachine),
+        _ => None
     }
 }
@@ -1,14 +1,58 @@
-use crate::randomizer::pool::Unlocks;
+use crate::cli::output::{self, ChallengeStatus, CharacterStatus, ContradictionReport, FixReport};
+use crate::cli::output::{HistoryReport, MarkStatus, OutputFormat, PlanReport, ProblemReport};
+use crate::cli::output::{RollReport, RunReport, StatusReport};
+use crate::cli::profiles;
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::challenges::Challenge;
+use crate::randomizer::characters::Character;
+use crate::randomizer::consistency::{Fix, RepairMode};
+use crate::randomizer::coop::CoopPick;
+use crate::randomizer::deadgod::{self, Task};
+use crate::randomizer::dependency::DependencyValue;
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::planner;
+use crate::randomizer::pool::{Exclusion, Unlocks};
+use crate::randomizer::route::Route;
 use crate::randomizer::targets::Target;
-use crate::toml_parse::savefile::Savefile;
+use crate::toml_parse::savefile::{Savefile, Severity};
 use crate::toml_parse::savefile::Error;
 
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::collections::HashSet;
+use strum::{EnumCount, IntoEnumIterator};
 use std::env;
+use std::fmt;
 use clap::{Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "srati", version = "0.1", about = "Smart Randomized Adventures of Tormented Isaac")]
 pub struct Cli {
+    /// roll with a fixed seed, the same savefile and seed always give the same pick
+    #[arg(long, value_name = "u64")]
+    pub seed: Option<u64>,
+
+    /// print every decision made while rolling the pick
+    #[arg(long)]
+    pub explain: bool,
+
+    /// print the floors, items and time limits of the rolled run
+    #[arg(long)]
+    pub route: bool,
+
+    /// roll a character for each player, all going for the same targets; co-op rolls are not
+    /// kept in the roll history
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u8).range(1..=4))]
+    pub players: u8,
+
+    /// use a named profile from the config directory instead of SRATI_SF or sf.toml
+    #[arg(long, value_name = "name")]
+    pub profile: Option<String>,
+
+    /// text, or json/toml to print one report with the same fields for every command
+    #[arg(long, value_name = "format", default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -62,12 +106,19 @@ pub enum Commands {
             \tHush\n\
             \tSatan\n\
             \tIsaac_Boss\n\
+            \tUltra_Greedier\n\
+            \tHome\n\
             \tMoms_Heart\n\
             Available Unlocks to set unlocked:\n\
             \tIt_Lives\n\
             \tPolaroid\n\
             \tNegative\n\
-            \tHoly_Mantle"
+            \tHoly_Mantle\n\
+            \tBlue_Womb\n\
+            \tThe_Void\n\
+            \tA_Secret_Exit\n\
+            \tA_Strange_Door\n\
+            Any other game achievement by its id, like Achievement_123"
     )]
     Unlock {
         #[arg(value_name = "characters/targets/unlocks")]
@@ -110,7 +161,7 @@ pub enum Commands {
             \tTainted_blue_baby\n\
             \tTainted_Eve\n\
             \tTainted_Samson\n\
-            \tTainted_Azazael\n\
+            \tTainted_Azazel\n\
             \tTainted_Lazarus\n\
             \tTainted_Eden\n\
             \tTainted_Lost\n\
@@ -118,7 +169,7 @@ pub enum Commands {
             \tTainted_Keeper\n\
             \tTainted_Apollyon\n\
             \tTainted_Forgotten\n\
-            \tTainted_Behtany\n\
+            \tTainted_Bethany\n\
             \tTainted_Jacob\n\
             Available Completion Marks to set:\n\
             \tBlue_Baby\n\
@@ -128,6 +179,8 @@ pub enum Commands {
             \tBeast\n\
             \tMother\n\
             \tUltra_Greed\n\
+            \tUltra_Greedier\n\
+            \tHome\n\
             \tHush\n\
             \tSatan\n\
             \tIsaac\n\
@@ -139,6 +192,10 @@ pub enum Commands {
         character: String,
         #[arg(value_name = "marks")]
         marks: Vec<String>,
+
+        /// the marks were done on hard
+        #[arg(long)]
+        hard: bool,
     },
 
     #[command(
@@ -158,7 +215,21 @@ pub enum Commands {
             Available options:\n\
             \tbossrush <chance from 0.0 to 1.0> -- chance to roll boss rush, 1.0 for 100%\n\
             \thush <chance from 0.0 to 1.0> -- chance to roll hush, 1.0 for 100%\n\
-            \tbossrushalt <true/false> -- roll boss rush on alt path (Mother/Beast); no true/false defaults to true"
+            \tbossrushalt <true/false> -- roll boss rush on alt path (Mother/Beast/Home); no true/false defaults to true\n\
+            \tweighting <uniform/remaining/inverse/manual> -- how characters and targets are picked:\n\
+            \t\tuniform -- everything is equally likely\n\
+            \t\tremaining -- more remaining marks means more likely, spreads progress evenly\n\
+            \t\tinverse -- less remaining marks means more likely, finishes stragglers first\n\
+            \t\tmanual -- characters are picked by weights set with `weight`\n\
+            \tweight <character>=<weight> -- weight of a character for manual weighting, defaults to 1.0\n\
+            \tnorepeat <rolls> -- don't roll a character that was rolled in the last <rolls> rolls, 0 to disable\n\
+            \tnorepeatpair <true/false> -- don't roll a target on a character it was already rolled with; no true/false defaults to true\n\
+            \tmaxrerolls <count> -- how many rerolls are allowed per reroll period, 0 for no limit\n\
+            \trerollperiod <session/day> -- count rerolls since the last done/failed roll or since midnight UTC\n\
+            \truleset <path/builtin> -- unlock rules file (.toml or .json), no path or builtin for the built-in rules\n\
+            \tpreferhard <true/false> -- roll marks only done on normal again to do them on hard; no true/false defaults to true\n\
+            \timplymarks <true/false> -- mark and done also mark the targets passed on the way, like Mom's Heart for Mega Satan; no true/false defaults to true\n\
+            \tcooptargets <any/all> -- with --players, roll targets useful for any of the characters or for all of them"
     )]
     Set {
         #[arg(value_name = "key")]
@@ -166,6 +237,386 @@ pub enum Commands {
         #[arg(value_name = "value")]
         value: Option<String>,
     },
+
+    #[command(
+        long_about = "import unlocks and completion marks from the game's persistentgamedata*.dat\n\
+            Prints what would change in the savefile, options set with `srati set` are kept"
+    )]
+    Import {
+        #[arg(value_name = "path")]
+        path: String,
+
+        /// only print the changes without writing the savefile
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    #[command(
+        long_about = "mark the targets of the last roll as completed\n\
+            Targets beaten on the way are marked too (e.g. Mom's Heart for Hush) unless\n\
+            `srati set implymarks false` was set\n\
+            For a full list of available targets use `srati mark --help`"
+    )]
+    Done {
+        /// only mark these targets of the roll
+        #[arg(long, value_name = "targets", num_args = 1..)]
+        only: Option<Vec<String>>,
+
+        /// the run was done on hard
+        #[arg(long)]
+        hard: bool,
+    },
+
+    #[command(long_about = "record the last roll as failed")]
+    Fail,
+
+    #[command(
+        long_about = "veto the last roll and roll again without its character and targets\n\
+            The number of rerolls can be limited with `srati set maxrerolls`"
+    )]
+    Reroll {
+        /// allow the same character again, only exclude the rolled targets
+        #[arg(long, conflicts_with = "keep_targets")]
+        keep_character: bool,
+
+        /// allow the same targets again, only exclude the rolled character
+        #[arg(long)]
+        keep_targets: bool,
+    },
+
+    #[command(
+        long_about = "show the completion mark grid, locked characters/targets and missing unlocks\n\
+            H is a completed hard mark, X is a completed normal mark, . is a mark left to do,\n\
+            - is a locked character or target"
+    )]
+    Status,
+
+    #[command(long_about = "show the last rolls and their outcomes")]
+    History {
+        #[arg(value_name = "count", default_value_t = 10)]
+        count: usize,
+    },
+
+    #[command(
+        long_about = "show the runs that unlock a locked character, target or unlock\n\
+            The goal is named like in the unlock command (tainted_lost, mother, holy_mantle),\n\
+            the runs start from the current savefile and are listed in the order to play them"
+    )]
+    Plan {
+        #[arg(value_name = "goal")]
+        goal: String,
+    },
+
+    #[command(
+        long_about = "print the whole unlock dependency graph\n\
+            Things already unlocked in the savefile are colored green, any of/all of nodes join\n\
+            several dependencies\n\
+            Available formats:\n\
+            \tdot\n\
+            \tmermaid"
+    )]
+    Graph {
+        #[arg(long, value_name = "format", default_value = "dot")]
+        format: String,
+    },
+
+    #[command(
+        long_about = "print the unlock rules in use, a starting point for a custom ruleset\n\
+            Set a ruleset file with `srati set ruleset <path>`, it replaces the built-in rules\n\
+            An all_of rule with a character and a target needs that character to beat the target,\n\
+            like Lazarus and Mom's Heart for Bethany\n\
+            Available formats:\n\
+            \ttoml\n\
+            \tjson"
+    )]
+    Rules {
+        #[arg(long, value_name = "format", default_value = "toml")]
+        format: String,
+    },
+
+    #[command(
+        long_about = "roll an uncompleted challenge that can be played with the current unlocks\n\
+            A challenge can be played once the target it ends on is unlocked. The achievements the\n\
+            game unlocks each challenge with aren't tracked, so a rolled challenge can still be\n\
+            locked in game, just roll again then\n\
+            Challenges are named by their number or like `cursed`, `its_in_the_cards`, `i_rule`"
+    )]
+    Challenge {
+        #[command(subcommand)]
+        action: Option<ChallengeAction>,
+    },
+
+    #[command(
+        long_about = "roll the most useful thing to do next on the way to 100% completion\n\
+            A rolled run, a rolled challenge and unfilled donation machines are compared by how\n\
+            many marks and unlocks they give, a run is recorded like a normal roll"
+    )]
+    Next,
+
+    #[command(
+        long_about = "set donation machines as filled up\n\
+            Available donation machines:\n\
+            \tDonation_Machine\n\
+            \tGreed_Donation_Machine"
+    )]
+    Donate {
+        #[arg(value_name = "machines")]
+        machines: Vec<String>,
+
+        /// set the machines as not filled up
+        #[arg(long)]
+        undo: bool,
+    },
+
+    #[command(
+        long_about = "manage named profiles, each one is a separate savefile\n\
+            Profiles are stored in $XDG_CONFIG_HOME/srati/profiles (~/.config/srati/profiles\n\
+            if it isn't set) and picked with `srati --profile <name> ...`\n\
+            Profile names can only contain letters, numbers, - and _"
+    )]
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+
+    #[command(
+        long_about = "check the savefile for problems\n\
+            Lists every unknown character, target or setting with its TOML key, marks for locked\n\
+            characters and targets, chances outside of 0-1 and an old version that needs migrating\n\
+            Errors keep the savefile from loading, warnings don't. The savefile isn't changed\n\
+            A savefile that loads is also checked for unlocks and marks the game can't have, see repair"
+    )]
+    Check,
+
+    #[command(
+        long_about = "fix unlocks and marks the game can't have, like a tainted character without Home\n\
+            or a Mother mark without Holy Mantle\n\
+            --infer unlocks everything the unlocks and marks depend on\n\
+            --strip locks what depends on something locked and removes its marks\n\
+            Without a mode the contradictions are only listed"
+    )]
+    Repair {
+        /// unlock what the unlocks and marks depend on
+        #[arg(long, conflicts_with = "strip")]
+        infer: bool,
+
+        /// lock what depends on something locked and remove the impossible marks
+        #[arg(long)]
+        strip: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ChallengeAction {
+    #[command(long_about = "mark challenges as completed")]
+    Done {
+        #[arg(value_name = "challenges")]
+        challenges: Vec<String>,
+    },
+
+    #[command(long_about = "remove challenges from the completed ones")]
+    Undo {
+        #[arg(value_name = "challenges")]
+        challenges: Vec<String>,
+    },
+
+    #[command(long_about = "list every challenge with its goal and state")]
+    List,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ProfileAction {
+    #[command(long_about = "list every profile")]
+    List,
+
+    #[command(long_about = "create a profile with a new savefile")]
+    Create {
+        #[arg(value_name = "name")]
+        name: String,
+    },
+
+    #[command(long_about = "create a profile with a copy of another profile's savefile")]
+    Copy {
+        #[arg(value_name = "from")]
+        from: String,
+
+        #[arg(value_name = "to")]
+        to: String,
+    },
+
+    #[command(long_about = "delete a profile and its savefile")]
+    Delete {
+        #[arg(value_name = "name")]
+        name: String,
+    },
+}
+
+pub fn print_plan(unlocks: &Unlocks, goal: &DependencyValue) {
+    let runs = match planner::plan(unlocks, goal) {
+        Ok(runs) => runs,
+        Err(e) => {
+            output::error(format!("Couldn't plan for {}: {}", goal, e));
+            output::exit(1);
+        }
+    };
+
+    if !output::is_text() {
+        output::report(|report| {
+            report.plan = Some(PlanReport {
+                goal: goal.to_string(),
+                runs: runs.iter().map(RunReport::from).collect(),
+            })
+        });
+        return;
+    }
+
+    if runs.is_empty() {
+        output::line(format!("{} is already unlocked", goal));
+        return;
+    }
+
+    output::line(format!("{} run(s) to unlock {}:", runs.len(), goal));
+    for (i, run) in runs.iter().enumerate() {
+        output::line(format!(
+            "{:>3}. {} vs {} (unlocks {})",
+            i + 1,
+            run.character(),
+            run.target(),
+            run.unlocks().join(", ")
+        ));
+    }
+}
+
+pub fn get_random_challenge(unlocks: &Unlocks, seed: Option<u64>) -> Option<Challenge> {
+    let challenge = match seed {
+        Some(seed) => unlocks.get_random_challenge(&mut ChaCha8Rng::seed_from_u64(seed)),
+        None => unlocks.get_random_challenge(&mut rand::thread_rng()),
+    };
+
+    match challenge {
+        Some(challenge) if output::is_text() => println!(
+            "Challenge #{}: {}\n\nVS\n\n{}",
+            challenge.number(),
+            challenge,
+            challenge.goal()
+        ),
+        Some(challenge) => output::report(|report| report.challenge = Some(challenge.into())),
+        None => output::line("couldn't roll a challenge ):"),
+    }
+
+    challenge
+}
+
+pub fn print_challenges(unlocks: &Unlocks) {
+    let state_of = |challenge| {
+        if unlocks.completed_challenges().contains(&challenge) {
+            "done"
+        } else if unlocks.is_challenge_unlocked(challenge) {
+            "todo"
+        } else {
+            "locked"
+        }
+    };
+
+    if !output::is_text() {
+        let challenges = Challenge::iter()
+            .map(|challenge| ChallengeStatus {
+                number: challenge.number(),
+                name: challenge.to_string(),
+                goal: challenge.goal().to_string(),
+                state: state_of(challenge),
+            })
+            .collect();
+        output::report(|report| report.challenges = Some(challenges));
+        return;
+    }
+
+    for challenge in Challenge::iter() {
+        let state = match state_of(challenge) {
+            "todo" => "to do",
+            state => state,
+        };
+
+        output::line(format!(
+            "{:>3}. {:<28} {:<12} {}",
+            challenge.number(),
+            challenge.to_string(),
+            challenge.goal().to_string(),
+            state
+        ));
+    }
+
+    output::line(format!(
+        "\n{}/{} challenges completed",
+        unlocks.completed_challenges().len(),
+        Challenge::COUNT
+    ));
+}
+
+pub fn get_next_task(unlocks: &Unlocks, seed: Option<u64>, explain: bool) -> Option<Task> {
+    let mut rng = match seed {
+        Some(seed) => ChaCha8Rng::seed_from_u64(seed),
+        None => ChaCha8Rng::from_entropy(),
+    };
+
+    if explain {
+        let candidates = deadgod::candidate_tasks(unlocks, &mut rng.clone());
+        let lines = candidates
+            .iter()
+            .map(|task| format!("{} (worth {})", task_name(task), task.worth()))
+            .collect();
+        print_why("candidates", lines);
+    }
+
+    let task = deadgod::next_task(unlocks, &mut rng);
+    match &task {
+        Some(task) => {
+            match task {
+                Task::Run { character, targets, .. } if !output::is_text() => {
+                    let roll = RollReport::new(&[*character], targets, false);
+                    output::report(|report| report.roll = Some(roll));
+                }
+                Task::Challenge(challenge) if !output::is_text() => {
+                    output::report(|report| report.challenge = Some((*challenge).into()));
+                }
+                _ => output::line(task_name(task)),
+            }
+            match task {
+                Task::Run { marks, unlocks, .. } if !unlocks.is_empty() => output::line(format!(
+                    "\n{} new mark(s), unlocks {}",
+                    marks,
+                    unlocks.join(", ")
+                )),
+                Task::Run { marks, .. } => output::line(format!("\n{} new mark(s)", marks)),
+                Task::Challenge(challenge) => output::line(format!(
+                    "\nmark it with `srati challenge done {}`",
+                    challenge.number()
+                )),
+                Task::Donation(_) => output::line("\nset it filled up with `srati donate`"),
+            }
+        }
+        None => output::line("Nothing left to do, Dead God!"),
+    }
+
+    task
+}
+
+fn task_name(task: &Task) -> String {
+    match task {
+        Task::Run { character, targets, .. } => {
+            let mut targs: Vec<&Target> = targets.iter().collect();
+            targs.sort();
+            let targs: Vec<String> = targs.iter().map(|targ| format!("{}", targ)).collect();
+            format!("{} VS {}", character, targs.join(", "))
+        }
+        Task::Challenge(challenge) => format!(
+            "Challenge #{}: {} VS {}",
+            challenge.number(),
+            challenge,
+            challenge.goal()
+        ),
+        Task::Donation(donation) => format!("Fill up the {}", donation),
+    }
 }
 
 pub struct SavefileInfo {
@@ -181,13 +632,13 @@ pub fn read_savefile() -> Option<SavefileInfo> {
             Unlocks::default()
         }
         Err(e) => {
-            eprintln!("{:?}", e);
+            output::error(e);
             return None;
         }
         Ok(s) => {
             match s.try_into() {
                 Err(e) => {
-                    eprintln!("{:?}", e);
+                    output::error(e);
                     return None;
                 }
                 Ok(u) => u,
@@ -196,13 +647,31 @@ pub fn read_savefile() -> Option<SavefileInfo> {
     };
 
     Some(SavefileInfo {
-        unlocks: unlocks,
+        unlocks,
         created_new_file,
     })
 }
 
-pub fn get_random_pick(unlocks: &Unlocks) {
-    match unlocks.get_random_pick() {
+pub fn get_random_pick(
+    unlocks: &Unlocks,
+    seed: Option<u64>,
+    exclusion: &Exclusion,
+    explain: bool,
+) -> Option<(Character, HashSet<Target>)> {
+    let (pick, trace) = match seed {
+        Some(seed) => unlocks.get_traced_random_pick(&mut ChaCha8Rng::seed_from_u64(seed), exclusion),
+        None => unlocks.get_traced_random_pick(&mut rand::thread_rng(), exclusion),
+    };
+
+    if explain {
+        print_why("why", trace.steps().iter().map(|step| step.to_string()).collect());
+    }
+
+    match &pick {
+        Some((ch, targs_set)) if !output::is_text() => {
+            let roll = RollReport::new(&[*ch], targs_set, unlocks.is_hard_roll(&[*ch], targs_set));
+            output::report(|report| report.roll = Some(roll));
+        }
         Some((ch, targs_set)) => {
             print!("{}\n\nVS\n\n", ch);
             let mut targs: Vec<&Target> = targs_set.iter().collect();
@@ -210,25 +679,478 @@ pub fn get_random_pick(unlocks: &Unlocks) {
             for targ in targs {
                 println!("{}", targ);
             }
+            if unlocks.is_hard_roll(&[*ch], targs_set) {
+                println!("\non Hard");
+            }
         }
         None => {
-            println!("couldn't roll ):");
+            output::line("couldn't roll ):");
         }
     }
+
+    pick
 }
 
-pub fn save_to_savefile(unlocks: &Unlocks) {
-    let savefile: Savefile = Into::into(unlocks);
-    match savefile.write_to_file(get_savefile_path().to_string()) {
+pub fn get_random_coop_pick(
+    unlocks: &Unlocks,
+    players: usize,
+    seed: Option<u64>,
+    explain: bool,
+) -> Option<CoopPick> {
+    let exclusion = Exclusion::default();
+    let (pick, trace) = match seed {
+        Some(seed) => unlocks.get_traced_coop_pick(players, &mut ChaCha8Rng::seed_from_u64(seed), &exclusion),
+        None => unlocks.get_traced_coop_pick(players, &mut rand::thread_rng(), &exclusion),
+    };
+
+    if explain {
+        print_why("why", trace.steps().iter().map(|step| step.to_string()).collect());
+    }
+
+    match &pick {
+        Some(pick) if !output::is_text() => {
+            let hard = unlocks.is_hard_roll(pick.characters(), pick.targets());
+            let roll = RollReport::new(pick.characters(), pick.targets(), hard);
+            output::report(|report| report.roll = Some(roll));
+        }
+        Some(pick) => {
+            let chars: Vec<String> = pick.characters().iter().map(|ch| ch.to_string()).collect();
+            print!("{}\n\nVS\n\n", chars.join(" & "));
+            let mut targs: Vec<&Target> = pick.targets().iter().collect();
+            targs.sort();
+            for targ in targs {
+                println!("{}", targ);
+            }
+            if unlocks.is_hard_roll(pick.characters(), pick.targets()) {
+                println!("\non Hard");
+            }
+        }
+        None => {
+            output::line("couldn't roll ):");
+        }
+    }
+
+    pick
+}
+
+pub fn print_route(targets: &HashSet<Target>) {
+    let route = Route::plan(targets);
+    if output::is_text() {
+        print!("\n{}", route);
+    } else {
+        output::report(|report| report.route = Some((&route).into()));
+    }
+}
+
+// the --explain lines of a roll
+fn print_why(header: &str, lines: Vec<String>) {
+    if !output::is_text() {
+        output::report(|report| report.why = lines);
+        return;
+    }
+
+    println!("{}:", header);
+    for (i, line) in lines.iter().enumerate() {
+        println!("{:>3}. {}", i + 1, line);
+    }
+    println!();
+}
+
+// completion mark columns of the status grid in the order of a completion note
+const STATUS_COLUMNS: [(Target, &str); 13] = [
+    (Target::Heart, "Heart"),
+    (Target::Isaac, "Isaac"),
+    (Target::Satan, "Satan"),
+    (Target::BossRush, "Rush"),
+    (Target::BlueBaby, "???"),
+    (Target::Lamb, "Lamb"),
+    (Target::MegaSatan, "MSatan"),
+    (Target::UltraGreed, "Greed"),
+    (Target::Greedier, "Greedr"),
+    (Target::Hush, "Hush"),
+    (Target::Delirium, "Deli"),
+    (Target::Mother, "Mother"),
+    (Target::Beast, "Beast"),
+];
+
+pub fn print_status(unlocks: &Unlocks) {
+    if !output::is_text() {
+        let status = get_status(unlocks);
+        output::report(|report| report.status = Some(status));
+        return;
+    }
+
+    let name_width = Character::iter()
+        .map(|ch| ch.to_string().len())
+        .max()
+        .unwrap_or(0);
+
+    print!("{:<width$}", "", width = name_width);
+    for (_, header) in STATUS_COLUMNS {
+        print!(" {:^6}", header);
+    }
+    println!("   done");
+
+    let mut total_marks = 0;
+    for ch in Character::iter() {
+        let is_unlocked = unlocks.unlocked_chars().contains(&ch);
+
+        print!("{:<width$}", ch.to_string(), width = name_width);
+        let mut completed = 0;
+        for (targ, _) in STATUS_COLUMNS {
+            let cell = if unlocks.has_mark(&ch, targ, Difficulty::Hard) {
+                completed += 1;
+                "H"
+            } else if unlocks.has_mark(&ch, targ, Difficulty::Normal) {
+                completed += 1;
+                "X"
+            } else if is_unlocked && unlocks.unlocked_targets().contains(&targ) {
+                "."
+            } else {
+                "-"
+            };
+            print!(" {:^6}", cell);
+        }
+        total_marks += completed;
+        println!(" {:>5.1}%", completed as f32 * 100. / STATUS_COLUMNS.len() as f32);
+    }
+
+    println!(
+        "\nTotal: {}/{} marks ({:.1}%)",
+        total_marks,
+        Character::COUNT * STATUS_COLUMNS.len(),
+        total_marks as f32 * 100. / (Character::COUNT * STATUS_COLUMNS.len()) as f32
+    );
+
+    let locked_chars: Vec<String> = Character::iter()
+        .filter(|ch| !unlocks.unlocked_chars().contains(ch))
+        .map(|ch| ch.to_string())
+        .collect();
+    if !locked_chars.is_empty() {
+        println!("\nLocked characters: {}", locked_chars.join(", "));
+    }
+
+    let locked_targets: Vec<String> = STATUS_COLUMNS
+        .iter()
+        .filter(|(targ, _)| !unlocks.unlocked_targets().contains(targ))
+        .map(|(targ, _)| targ.to_string())
+        .collect();
+    if !locked_targets.is_empty() {
+        println!("Locked targets: {}", locked_targets.join(", "));
+    }
+
+    let missing_unlocks: Vec<String> = Achievement::TRACKED
+        .into_iter()
+        .filter(|achievement| !unlocks.is_achievement_unlocked(*achievement))
+        .map(|achievement| achievement.to_string())
+        .collect();
+    if !missing_unlocks.is_empty() {
+        println!("Missing unlocks: {}", missing_unlocks.join(", "));
+    }
+}
+
+fn get_status(unlocks: &Unlocks) -> StatusReport {
+    let characters: Vec<CharacterStatus> = Character::iter()
+        .map(|ch| {
+            let is_unlocked = unlocks.unlocked_chars().contains(&ch);
+            let marks = STATUS_COLUMNS
+                .iter()
+                .map(|(targ, _)| MarkStatus {
+                    target: targ.to_string(),
+                    state: if unlocks.has_mark(&ch, *targ, Difficulty::Hard) {
+                        "hard"
+                    } else if unlocks.has_mark(&ch, *targ, Difficulty::Normal) {
+                        "normal"
+                    } else if is_unlocked && unlocks.unlocked_targets().contains(targ) {
+                        "todo"
+                    } else {
+                        "locked"
+                    },
+                })
+                .collect();
+            CharacterStatus {
+                name: ch.to_string(),
+                unlocked: is_unlocked,
+                marks,
+            }
+        })
+        .collect();
+
+    StatusReport {
+        total_marks: characters
+            .iter()
+            .flat_map(|ch| &ch.marks)
+            .filter(|mark| mark.state == "hard" || mark.state == "normal")
+            .count(),
+        max_marks: Character::COUNT * STATUS_COLUMNS.len(),
+        locked_characters: Character::iter()
+            .filter(|ch| !unlocks.unlocked_chars().contains(ch))
+            .map(|ch| ch.to_string())
+            .collect(),
+        locked_targets: STATUS_COLUMNS
+            .iter()
+            .filter(|(targ, _)| !unlocks.unlocked_targets().contains(targ))
+            .map(|(targ, _)| targ.to_string())
+            .collect(),
+        missing_unlocks: Achievement::TRACKED
+            .into_iter()
+            .filter(|achievement| !unlocks.is_achievement_unlocked(*achievement))
+            .map(|achievement| achievement.to_string())
+            .collect(),
+        characters,
+    }
+}
+
+pub fn print_history(unlocks: &Unlocks, count: usize) {
+    let history = unlocks.history();
+    let shown = history.iter().skip(history.len().saturating_sub(count));
+    if !output::is_text() {
+        let records = shown.map(HistoryReport::from).collect();
+        output::report(|report| report.history = Some(records));
+        return;
+    }
+
+    if history.is_empty() {
+        println!("Nothing was rolled yet.");
+        return;
+    }
+
+    for record in shown {
+        let mut targs: Vec<&Target> = record.targets().iter().collect();
+        targs.sort();
+        let targs: Vec<String> = targs.iter().map(|targ| format!("{}", targ)).collect();
+
+        println!(
+            "{}  {:<6}  {} VS {}",
+            format_timestamp(record.timestamp()),
+            record.outcome(),
+            record.character(),
+            targs.join(", ")
+        );
+    }
+}
+
+// unix timestamp to "YYYY-MM-DD HH:MM" in UTC
+fn format_timestamp(timestamp: u64) -> String {
+    let days = (timestamp / 86400) as i64;
+    let secs_of_day = timestamp % 86400;
+
+    // days since epoch to civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
+
+pub fn print_unlocks_diff(old: &Unlocks, new: &Unlocks) -> bool {
+    let mut changes: Vec<String> = Vec::new();
+
+    let mut chars: Vec<&Character> = old
+        .unlocked_chars()
+        .union(new.unlocked_chars())
+        .collect();
+    chars.sort_by_key(|ch| **ch as u32);
+    for ch in chars {
+        match (old.unlocked_chars().contains(ch), new.unlocked_chars().contains(ch)) {
+            (false, true) => changes.push(format!("+ character {}", ch)),
+            (true, false) => changes.push(format!("- character {}", ch)),
+            _ => {}
+        }
+    }
+
+    let mut targs: Vec<&Target> = old
+        .unlocked_targets()
+        .union(new.unlocked_targets())
+        .collect();
+    targs.sort_by_key(|targ| **targ as u32);
+    for targ in targs {
+        match (old.unlocked_targets().contains(targ), new.unlocked_targets().contains(targ)) {
+            (false, true) => changes.push(format!("+ target {}", targ)),
+            (true, false) => changes.push(format!("- target {}", targ)),
+            _ => {}
+        }
+    }
+
+    for achievement in Achievement::iter() {
+        let name = achievement.to_string();
+        match (old.is_achievement_unlocked(achievement), new.is_achievement_unlocked(achievement)) {
+            (false, true) => changes.push(format!("+ unlock {}", name)),
+            (true, false) => changes.push(format!("- unlock {}", name)),
+            _ => {}
+        }
+    }
+
+    let empty = HashSet::new();
+    let mut marked_chars: Vec<&Character> = old.marks().keys().chain(new.marks().keys()).collect();
+    marked_chars.sort_by_key(|ch| **ch as u32);
+    marked_chars.dedup();
+    for ch in marked_chars {
+        let old_marks = old.marks().get(ch).unwrap_or(&empty);
+        let new_marks = new.marks().get(ch).unwrap_or(&empty);
+
+        let mut added: Vec<&(Target, Difficulty)> = new_marks.difference(old_marks).collect();
+        added.sort_by_key(|(targ, diff)| (*targ as u32, *diff as u32));
+        for (targ, diff) in added {
+            changes.push(format!("+ mark {}: {}{}", ch, targ, hard_suffix(diff)));
+        }
+
+        let mut removed: Vec<&(Target, Difficulty)> = old_marks.difference(new_marks).collect();
+        removed.sort_by_key(|(targ, diff)| (*targ as u32, *diff as u32));
+        for (targ, diff) in removed {
+            changes.push(format!("- mark {}: {}{}", ch, targ, hard_suffix(diff)));
+        }
+    }
+
+    if changes.is_empty() {
+        output::line("Nothing to change.");
+        return false;
+    }
+
+    for change in changes {
+        output::line(change);
+    }
+    true
+}
+
+fn hard_suffix(difficulty: &Difficulty) -> &'static str {
+    match difficulty {
+        Difficulty::Normal => "",
+        Difficulty::Hard => " (Hard)",
+    }
+}
+
+// prints every problem in the savefile, returns whether it can be loaded
+pub fn check_savefile() -> bool {
+    let path = get_savefile_path();
+    let (savefile, problems) = match Savefile::check_file(path.clone()) {
+        Err(e) => {
+            check_error(format!("{}: {}", path, e));
+            return false;
+        }
+        Ok(checked) => checked,
+    };
+
+    let errors = problems
+        .iter()
+        .filter(|problem| problem.severity() == Severity::Error)
+        .count();
+    output::report(|report| {
+        report.problems = Some(problems.iter().map(ProblemReport::from).collect());
+        report.errors.extend(
+            problems
+                .iter()
+                .filter(|problem| problem.severity() == Severity::Error)
+                .map(|problem| format!("{}: {}", problem.location(), problem.message())),
+        );
+    });
+    if output::is_text() {
+        for problem in &problems {
+            output::line(problem);
+        }
+    }
+    if !problems.is_empty() && output::is_text() {
+        output::line(format!(
+            "\n{} errors and {} warnings in {}\n",
+            errors,
+            problems.len() - errors,
+            path
+        ));
+    }
+    if errors > 0 {
+        return false;
+    }
+
+    // the ruleset can still fail to load
+    let unlocks: Unlocks = match savefile.try_into() {
         Err(e) => {
-            eprintln!("{:?}", e);
-            return;
+            check_error(e);
+            return false;
         }
-        Ok(_) => {},
+        Ok(u) => u,
+    };
+    if !print_contradictions(&unlocks) && problems.is_empty() {
+        output::line(format!("No problems found in {}", path));
+    }
+
+    true
+}
+
+// check prints its errors next to the problems, the structured formats list them as errors
+fn check_error(msg: impl fmt::Display) {
+    if output::is_text() {
+        output::line(format!("error: {}", msg));
+    } else {
+        output::error(msg);
+    }
+}
+
+// returns whether there were any
+pub fn print_contradictions(unlocks: &Unlocks) -> bool {
+    let contradictions = unlocks.validate();
+    if !output::is_text() {
+        output::report(|report| {
+            report.contradictions =
+                Some(contradictions.iter().map(ContradictionReport::from).collect())
+        });
+        return !contradictions.is_empty();
+    }
+
+    for contradiction in &contradictions {
+        output::line(format!("contradiction: {}", contradiction));
+    }
+    if !contradictions.is_empty() {
+        output::line(format!(
+            "\n{} contradictions, fix them with `srati repair --infer` or `srati repair --strip`",
+            contradictions.len()
+        ));
+    }
+
+    !contradictions.is_empty()
+}
+
+pub fn print_fixes(fixes: &[Fix], mode: RepairMode) {
+    if !output::is_text() {
+        output::report(|report| report.fixes = Some(fixes.iter().map(FixReport::from).collect()));
+        return;
+    }
+
+    if fixes.is_empty() {
+        output::line("Nothing to repair.");
+        return;
+    }
+
+    for fix in fixes {
+        output::line(fix);
+    }
+    output::line(format!("\n{} changes made with {}", fixes.len(), mode));
+}
+
+pub fn save_to_savefile(unlocks: &Unlocks) {
+    let savefile: Savefile = Into::into(unlocks);
+    if let Err(e) = savefile.write_to_file(get_savefile_path().to_string()) {
+        output::error(e);
     }
 }
 
 fn get_savefile_path() -> String {
+    if let Some(path) = profiles::active_path() {
+        return path.to_string_lossy().to_string();
+    }
+
     match env::var("SRATI_SF") {
         Ok(val) => val,
         Err(_) => "sf.toml".to_string(),
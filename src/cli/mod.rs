@@ -1,12 +1,31 @@
 mod commands;
 mod cli_structs;
+mod output;
+mod profiles;
 
+use crate::dat_parse::gamedata::GameData;
+use crate::randomizer::consistency::RepairMode;
+use crate::randomizer::coop::CoopTargets;
+use crate::randomizer::graph::{self, GraphFormat};
+use crate::randomizer::deadgod::Task;
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::history::{now_timestamp, Outcome, RerollPeriod};
+use crate::randomizer::pool::{Exclusion, Unlocks};
+use crate::randomizer::ruleset::Ruleset;
+use crate::randomizer::weighting::Weighting;
+use crate::toml_parse::ruleset::{read_ruleset, RulesetFile};
 use clap::{Parser, CommandFactory};
 use commands::*;
 use cli_structs::*;
 use std::collections::HashSet;
+use std::str::FromStr;
 
 fn print_help(subcommand: &str) -> ! {
+    if !output::is_text() {
+        output::error(format!("Missing arguments, see `srati {} --help`", subcommand));
+        output::exit(1);
+    }
+
     Cli::command()
         .find_subcommand_mut(subcommand)
         .unwrap()
@@ -17,19 +36,108 @@ fn print_help(subcommand: &str) -> ! {
 
 macro_rules! print_help_msg {
     ($subcmd:expr, $fmt:expr $(, $arg:expr )* $(,)? ) => {{
-        Cli::command()
-            .find_subcommand_mut($subcmd)
-            .expect(concat!("unknown subcommand: ", $subcmd))
-            .print_help()
-            .unwrap();
-        eprintln!("\n{}", format!($fmt $(, $arg )*));
-        std::process::exit(1);
+        if output::is_text() {
+            Cli::command()
+                .find_subcommand_mut($subcmd)
+                .expect(concat!("unknown subcommand: ", $subcmd))
+                .print_help()
+                .unwrap();
+            eprintln!();
+        }
+        output::error(format!($fmt $(, $arg )*));
+        output::exit(1);
     }};
 }
 
+fn command_name(command: &Option<Commands>) -> &'static str {
+    match command {
+        Some(Commands::Unlock { .. }) => "unlock",
+        Some(Commands::Ununlock { .. }) => "ununlock",
+        Some(Commands::Mark { .. }) => "mark",
+        Some(Commands::Unmark { .. }) => "unmark",
+        Some(Commands::Set { .. }) => "set",
+        Some(Commands::Import { .. }) => "import",
+        Some(Commands::Done { .. }) => "done",
+        Some(Commands::Fail) => "fail",
+        Some(Commands::Reroll { .. }) => "reroll",
+        Some(Commands::Status) => "status",
+        Some(Commands::History { .. }) => "history",
+        Some(Commands::Plan { .. }) => "plan",
+        Some(Commands::Graph { .. }) => "graph",
+        Some(Commands::Rules { .. }) => "rules",
+        Some(Commands::Challenge { .. }) => "challenge",
+        Some(Commands::Next) => "next",
+        Some(Commands::Donate { .. }) => "donate",
+        Some(Commands::Profile { .. }) => "profile",
+        Some(Commands::Check) => "check",
+        Some(Commands::Repair { .. }) => "repair",
+        None => "roll",
+    }
+}
+
 pub fn parse_cmd() {
-    let cli = Cli::parse();
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(e) => exit_with_parse_error(e),
+    };
+    output::init(cli.output, command_name(&cli.command));
+
+    if let Some(name) = &cli.profile {
+        if !matches!(cli.command, Some(Commands::Profile { .. })) {
+            if let Err(e) = profiles::set_active(name) {
+                output::error(e);
+                output::exit(1);
+            }
+        }
+    }
 
+    run(cli);
+    output::finish();
+}
+
+// the arguments couldn't be parsed, so the format and the command for the report are looked for
+// in them directly. Help and version are printed as text
+fn exit_with_parse_error(e: clap::Error) -> ! {
+    use clap::error::ErrorKind;
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let format = args
+        .iter()
+        .enumerate()
+        .find_map(|(i, arg)| match arg.strip_prefix("--output") {
+            Some("") => args.get(i + 1).map(|val| val.as_str()),
+            Some(val) => val.strip_prefix('='),
+            None => None,
+        })
+        .and_then(|val| output::OutputFormat::from_str(val).ok())
+        .unwrap_or_default();
+    if format == output::OutputFormat::Text || !e.use_stderr() {
+        e.exit();
+    }
+
+    let cmd = Cli::command();
+    let subcommand = args
+        .iter()
+        .find_map(|arg| cmd.find_subcommand(arg))
+        .map(|subcommand| subcommand.get_name().to_string());
+    output::init(format, subcommand.as_deref().unwrap_or("roll"));
+
+    let msg = match e.kind() {
+        ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand => format!(
+            "Missing arguments, see `srati {} --help`",
+            subcommand.as_deref().unwrap_or_default()
+        ),
+        _ => {
+            let rendered = e.to_string();
+            let first_line = rendered.lines().next().unwrap_or_default();
+            first_line.trim_start_matches("error: ").to_string()
+        }
+    };
+    output::error(msg);
+    output::exit(2)
+}
+
+fn run(cli: Cli) {
     match cli.command {
         Some(Commands::Unlock { unlocks }) => {
             if unlocks.is_empty() {
@@ -38,7 +146,7 @@ pub fn parse_cmd() {
 
             let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
-                None => std::process::exit(1)
+                None => output::exit(1)
             };
             let mut savefile_updated = false;
 
@@ -48,17 +156,12 @@ pub fn parse_cmd() {
                         match u {
                             Unlock::Character(char) => file_unlocks.add_unlocked_chars(HashSet::from([char])),
                             Unlock::Target(targ) => file_unlocks.add_unlocked_targets(HashSet::from([targ])),
-                            Unlock::Unlockable(unl) => match unl {
-                                Unlockable::ItLives => file_unlocks.set_it_lives_unlocked(true),
-                                Unlockable::Polaroid => file_unlocks.set_polaroid_unlocked(true),
-                                Unlockable::Negative => file_unlocks.set_negative_unlocked(true),
-                                Unlockable::HolyMantle => file_unlocks.set_mantle_unlocked(true),
-                            }
+                            Unlock::Achievement(achievement) => file_unlocks.set_achievement_unlocked(achievement, true),
                         };
                         savefile_updated = true;
                     }
                     None => {
-                        println!("Skipping unlocking {}: no such item found.", item);
+                        output::skipped(&item, format!("unlocking {}", item), "no such item found");
                     }
                 };
             }
@@ -75,7 +178,7 @@ pub fn parse_cmd() {
 
             let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
-                None => std::process::exit(1)
+                None => output::exit(1)
             };
             let mut savefile_updated = false;
 
@@ -85,17 +188,12 @@ pub fn parse_cmd() {
                         match u {
                             Unlock::Character(char) => file_unlocks.remove_unlocked_chars(&HashSet::from([char])),
                             Unlock::Target(targ) => file_unlocks.remove_unlocked_targets(&HashSet::from([targ])),
-                            Unlock::Unlockable(unl) => match unl {
-                                Unlockable::ItLives => file_unlocks.set_it_lives_unlocked(false),
-                                Unlockable::Polaroid => file_unlocks.set_polaroid_unlocked(false),
-                                Unlockable::Negative => file_unlocks.set_negative_unlocked(false),
-                                Unlockable::HolyMantle => file_unlocks.set_mantle_unlocked(false),
-                            }
+                            Unlock::Achievement(achievement) => file_unlocks.set_achievement_unlocked(achievement, false),
                         };
                         savefile_updated = true;
                     }
                     None => {
-                        println!("Skipping locking {}: no such item found.", item);
+                        output::skipped(&item, format!("locking {}", item), "no such item found");
                     }
                 };
             }
@@ -105,7 +203,7 @@ pub fn parse_cmd() {
             }
         }
 
-        Some(Commands::Mark { character: char_str, marks: marks_strs }) => {
+        Some(Commands::Mark { character: char_str, marks: marks_strs, hard }) => {
             if char_str.is_empty() || marks_strs.is_empty() {
                 print_help("mark");
             }
@@ -117,18 +215,19 @@ pub fn parse_cmd() {
 
             let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
-                None => std::process::exit(1)
+                None => output::exit(1)
             };
             let mut savefile_updated = false;
+            let difficulty = if hard { Difficulty::Hard } else { Difficulty::Normal };
 
             for mark_str in marks_strs {
                 match Unlock::try_str_to_target(mark_str.as_str(), false) {
                     Some(targ) => {
-                        file_unlocks.add_marks(char, HashSet::from([targ]));
+                        file_unlocks.add_marks(char, HashSet::from([targ]), difficulty);
                         savefile_updated = true;
                     }
                     None => {
-                        println!("Skipping adding mark {} to character {}: no such target found.", mark_str, char_str);
+                        output::skipped(&mark_str, format!("adding mark {} to character {}", mark_str, char_str), "no such target found");
                     }
                 }
             }
@@ -150,7 +249,7 @@ pub fn parse_cmd() {
 
             let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
-                None => std::process::exit(1)
+                None => output::exit(1)
             };
             let mut savefile_updated = false;
 
@@ -163,7 +262,7 @@ pub fn parse_cmd() {
                                 savefile_updated = true;
                             }
                             None => {
-                                println!("Skipping removing mark {} from character {}: no such target found.", mark_str, char_str);
+                                output::skipped(&mark_str, format!("removing mark {} from character {}", mark_str, char_str), "no such target found");
                             }
                         }
                     }
@@ -186,7 +285,7 @@ pub fn parse_cmd() {
 
             let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
-                None => std::process::exit(1)
+                None => output::exit(1)
             };
             let savefile_updated;
 
@@ -196,7 +295,7 @@ pub fn parse_cmd() {
                         Some(val) => {
                             match val.parse::<f32>() {
                                 Ok(chance) => {
-                                    if chance >= 0. && chance <= 1. {
+                                    if (0. ..=1.).contains(&chance) {
                                         file_unlocks.set_boss_rush_chance(chance);
                                         savefile_updated = true;
                                     } else {
@@ -218,7 +317,7 @@ pub fn parse_cmd() {
                         Some(val) => {
                             match val.parse::<f32>() {
                                 Ok(chance) => {
-                                    if chance >= 0. && chance <= 1. {
+                                    if (0. ..=1.).contains(&chance) {
                                         file_unlocks.set_hush_chance(chance);
                                         savefile_updated = true;
                                     } else {
@@ -254,6 +353,199 @@ pub fn parse_cmd() {
                         }
                     }
                 }
+                "weighting" => {
+                    match value {
+                        Some(val) => {
+                            match Weighting::from_str(val.to_lowercase().as_str()) {
+                                Ok(weighting) => {
+                                    file_unlocks.set_weighting(weighting);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Weighting must be one of uniform/remaining/inverse/manual (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Weighting was not provided");
+                        }
+                    }
+                }
+                "weight" => {
+                    match value {
+                        Some(val) => {
+                            let Some((char_str, weight_str)) = val.split_once('=') else {
+                                print_help_msg!("set", "Weight must be given as <character>=<weight> (your input was {})", val);
+                            };
+
+                            let char = match Unlock::try_str_to_character(char_str.to_lowercase().as_str()) {
+                                Some(c) => c,
+                                None => print_help_msg!("set", "No such character: {}", char_str),
+                            };
+
+                            match weight_str.parse::<f32>() {
+                                Ok(weight) if weight >= 0. => {
+                                    file_unlocks.set_manual_weight(char, weight);
+                                    savefile_updated = true;
+                                }
+                                _ => {
+                                    print_help_msg!("set", "Weight must be a non-negative number (your input was {})", weight_str);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Weight was not provided");
+                        }
+                    }
+                }
+                "norepeat" => {
+                    match value {
+                        Some(val) => {
+                            match val.parse::<usize>() {
+                                Ok(rolls) => {
+                                    file_unlocks.set_no_repeat_char_within(rolls);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Must input a number of rolls (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Number of rolls was not provided");
+                        }
+                    }
+                }
+                "norepeatpair" => {
+                    match value {
+                        Some(val) => {
+                            match val.parse::<bool>() {
+                                Ok(b) => {
+                                    file_unlocks.set_no_repeat_pair(b);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Must input true/false value (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            file_unlocks.set_no_repeat_pair(true);
+                            savefile_updated = true;
+                        }
+                    }
+                }
+                "maxrerolls" => {
+                    match value {
+                        Some(val) => {
+                            match val.parse::<usize>() {
+                                Ok(count) => {
+                                    file_unlocks.set_max_rerolls(count);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Must input a number of rerolls (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Number of rerolls was not provided");
+                        }
+                    }
+                }
+                "rerollperiod" => {
+                    match value {
+                        Some(val) => {
+                            match RerollPeriod::from_str(val.to_lowercase().as_str()) {
+                                Ok(period) => {
+                                    file_unlocks.set_reroll_period(period);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Reroll period must be session or day (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Reroll period was not provided");
+                        }
+                    }
+                }
+                "preferhard" => {
+                    match value {
+                        Some(val) => {
+                            match val.parse::<bool>() {
+                                Ok(b) => {
+                                    file_unlocks.set_prefer_hard(b);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Must input true/false value (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            file_unlocks.set_prefer_hard(true);
+                            savefile_updated = true;
+                        }
+                    }
+                }
+                "implymarks" => {
+                    match value {
+                        Some(val) => {
+                            match val.parse::<bool>() {
+                                Ok(b) => {
+                                    file_unlocks.set_imply_marks(b);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Must input true/false value (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            file_unlocks.set_imply_marks(true);
+                            savefile_updated = true;
+                        }
+                    }
+                }
+                "cooptargets" => {
+                    match value {
+                        Some(val) => {
+                            match CoopTargets::from_str(val.to_lowercase().as_str()) {
+                                Ok(coop_targets) => {
+                                    file_unlocks.set_coop_targets(coop_targets);
+                                    savefile_updated = true;
+                                }
+                                Err(_) => {
+                                    print_help_msg!("set", "Co-op targets must be any or all (your input was {})", val);
+                                }
+                            }
+                        }
+                        None => {
+                            print_help_msg!("set", "Co-op targets were not provided");
+                        }
+                    }
+                }
+                "ruleset" => {
+                    match value {
+                        Some(path) if path != "builtin" => {
+                            match read_ruleset(path.as_str()) {
+                                Ok(ruleset) => {
+                                    file_unlocks.set_ruleset(ruleset);
+                                    savefile_updated = true;
+                                }
+                                Err(e) => {
+                                    print_help_msg!("set", "Couldn't use ruleset {}: {}", path, e);
+                                }
+                            }
+                        }
+                        _ => {
+                            file_unlocks.set_ruleset(Ruleset::builtin());
+                            savefile_updated = true;
+                        }
+                    }
+                }
                 _ => {
                     print_help("set");
                 }
@@ -264,13 +556,376 @@ pub fn parse_cmd() {
             }
         }
 
+        Some(Commands::Import { path, dry_run }) => {
+            let SavefileInfo { unlocks: file_unlocks, created_new_file } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let game_data = match GameData::read_from_file(path) {
+                Ok(d) => d,
+                Err(e) => {
+                    output::error(e);
+                    output::exit(1);
+                }
+            };
+
+            let mut imported = Unlocks::from(&game_data);
+            imported.copy_settings_from(&file_unlocks);
+            // challenges and donations aren't read from the game data, keep the ones in the savefile
+            for challenge in file_unlocks.completed_challenges() {
+                imported.set_challenge_completed(*challenge, true);
+            }
+            for donation in file_unlocks.completed_donations() {
+                imported.set_donation_completed(*donation, true);
+            }
+
+            let has_changes = print_unlocks_diff(&file_unlocks, &imported);
+
+            if !dry_run && (has_changes || created_new_file) {
+                save_to_savefile(&imported);
+            }
+        }
+
+        Some(Commands::Done { only, hard }) => {
+            let SavefileInfo { unlocks: mut file_unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let (char, rolled) = match file_unlocks.pending_roll() {
+                Some(record) => (record.character(), record.targets().clone()),
+                None => {
+                    output::error("Nothing to mark: the last roll was already reported.");
+                    output::exit(1);
+                }
+            };
+
+            let completed = match only {
+                Some(targ_strs) => {
+                    let mut completed = HashSet::new();
+                    for targ_str in targ_strs {
+                        match Unlock::try_str_to_target(targ_str.to_lowercase().as_str(), false) {
+                            Some(targ) if rolled.contains(&targ) => {
+                                completed.insert(targ);
+                            }
+                            Some(targ) => {
+                                output::skipped(&targ_str, format!("marking {}", targ), "it was not rolled");
+                            }
+                            None => {
+                                output::skipped(&targ_str, format!("marking {}", targ_str), "no such target found");
+                            }
+                        }
+                    }
+                    completed
+                }
+                None => rolled,
+            };
+
+            if completed.is_empty() {
+                print_help("done");
+            }
+
+            let difficulty = if hard { Difficulty::Hard } else { Difficulty::Normal };
+            file_unlocks.add_marks(char, completed, difficulty)
+                .set_pending_roll_outcome(Outcome::Done);
+            save_to_savefile(&file_unlocks);
+        }
+
+        Some(Commands::Fail) => {
+            let SavefileInfo { unlocks: mut file_unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            if file_unlocks.pending_roll().is_none() {
+                output::error("Nothing to fail: the last roll was already reported.");
+                output::exit(1);
+            }
+
+            file_unlocks.set_pending_roll_outcome(Outcome::Failed);
+            save_to_savefile(&file_unlocks);
+        }
+
+        Some(Commands::Reroll { keep_character, keep_targets }) => {
+            let SavefileInfo { unlocks: mut file_unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let (char, targs) = match file_unlocks.pending_roll() {
+                Some(record) => (record.character(), record.targets().clone()),
+                None => {
+                    output::error("Nothing to reroll: the last roll was already reported.");
+                    output::exit(1);
+                }
+            };
+
+            if file_unlocks.rerolls_left(now_timestamp()) == Some(0) {
+                output::error("No rerolls left, finish or fail the current roll first.");
+                output::exit(1);
+            }
+
+            let mut exclusion = Exclusion::new(
+                if keep_character { HashSet::new() } else { HashSet::from([char]) },
+                if keep_targets { HashSet::new() } else { targs },
+            );
+            if keep_character {
+                exclusion.set_kept_character(char);
+            }
+
+            if let Some((ch, targs)) = get_random_pick(&file_unlocks, cli.seed, &exclusion, cli.explain) {
+                if cli.route {
+                    print_route(&targs);
+                }
+                file_unlocks.set_pending_roll_outcome(Outcome::Vetoed)
+                    .record_roll(ch, targs);
+                save_to_savefile(&file_unlocks);
+
+                if let Some(left) = file_unlocks.rerolls_left(now_timestamp()) {
+                    output::line(format!("\n{} rerolls left", left));
+                    output::report(|report| report.rerolls_left = Some(left));
+                }
+            } else {
+                output::exit(1);
+            }
+        }
+
+        Some(Commands::Status) => {
+            let SavefileInfo { unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+            print_status(&unlocks);
+        }
+
+        Some(Commands::History { count }) => {
+            let SavefileInfo { unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+            print_history(&unlocks, count);
+        }
+
+        Some(Commands::Plan { goal: goal_str }) => {
+            let goal = match Unlock::from_unlock_arg(goal_str.as_str()) {
+                Some(unlock) => unlock.to_dependency_value(),
+                None => print_help_msg!("plan", "No such character, target or unlock: {}", goal_str),
+            };
+
+            let SavefileInfo { unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+            print_plan(&unlocks, &goal);
+        }
+
+        Some(Commands::Graph { format: format_str }) => {
+            let format = match GraphFormat::from_str(format_str.to_lowercase().as_str()) {
+                Ok(format) => format,
+                Err(_) => print_help_msg!("graph", "No such graph format: {}", format_str),
+            };
+
+            let SavefileInfo { unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+            let graph = graph::render(&unlocks, format);
+            if output::is_text() {
+                output::line(graph.trim_end());
+            } else {
+                output::report(|report| report.graph = Some(graph));
+            }
+        }
+
+        Some(Commands::Rules { format }) => {
+            let SavefileInfo { unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let file = RulesetFile::from(unlocks.ruleset());
+            let contents = match format.to_lowercase().as_str() {
+                "toml" => file.to_toml_string(),
+                "json" => file.to_json_string(),
+                _ => print_help_msg!("rules", "No such ruleset format: {}", format),
+            };
+            match contents {
+                Ok(_) if !output::is_text() => output::report(|report| report.rules = Some(file)),
+                Ok(contents) => output::line(contents),
+                Err(e) => {
+                    output::error(e);
+                    output::exit(1);
+                }
+            }
+        }
+
+        Some(Commands::Challenge { action }) => {
+            let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let (challenge_strs, is_completed) = match action {
+                None => {
+                    get_random_challenge(&file_unlocks, cli.seed);
+                    if created_new_file {
+                        save_to_savefile(&file_unlocks);
+                    }
+                    return;
+                }
+                Some(ChallengeAction::List) => {
+                    print_challenges(&file_unlocks);
+                    return;
+                }
+                Some(ChallengeAction::Done { challenges }) => (challenges, true),
+                Some(ChallengeAction::Undo { challenges }) => (challenges, false),
+            };
+
+            if challenge_strs.is_empty() {
+                print_help("challenge");
+            }
+
+            let mut savefile_updated = false;
+            for challenge_str in challenge_strs {
+                match try_str_to_challenge(challenge_str.as_str()) {
+                    Some(challenge) => {
+                        file_unlocks.set_challenge_completed(challenge, is_completed);
+                        savefile_updated = true;
+                    }
+                    None => {
+                        output::skipped(&challenge_str, format!("challenge {}", challenge_str), "no such challenge found");
+                    }
+                }
+            }
+
+            if savefile_updated || created_new_file {
+                save_to_savefile(&file_unlocks);
+            }
+        }
+
+        Some(Commands::Next) => {
+            let SavefileInfo { mut unlocks, created_new_file } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let task = get_next_task(&unlocks, cli.seed, cli.explain);
+            let rolled_run = matches!(task, Some(Task::Run { .. }));
+            if let Some(Task::Run { character, targets, .. }) = task {
+                if cli.route {
+                    print_route(&targets);
+                }
+                unlocks.record_roll(character, targets);
+            }
+            if rolled_run || created_new_file {
+                save_to_savefile(&unlocks);
+            }
+        }
+
+        Some(Commands::Donate { machines, undo }) => {
+            if machines.is_empty() {
+                print_help("donate");
+            }
+
+            let SavefileInfo { unlocks: mut file_unlocks, created_new_file } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+            let mut savefile_updated = false;
+
+            for machine in machines {
+                match try_str_to_donation(machine.to_lowercase().as_str()) {
+                    Some(donation) => {
+                        file_unlocks.set_donation_completed(donation, !undo);
+                        savefile_updated = true;
+                    }
+                    None => {
+                        output::skipped(&machine, format!("donation machine {}", machine), "no such machine found");
+                    }
+                }
+            }
+
+            if savefile_updated || created_new_file {
+                save_to_savefile(&file_unlocks);
+            }
+        }
+
+        Some(Commands::Profile { action }) => {
+            let result = match action {
+                ProfileAction::List => profiles::list().map(|names| {
+                    if names.is_empty() {
+                        output::line("No profiles yet, create one with `srati profile create <name>`");
+                    }
+                    for name in names {
+                        output::line(name);
+                    }
+                }),
+                ProfileAction::Create { name } => profiles::create(name.as_str()),
+                ProfileAction::Copy { from, to } => profiles::copy(from.as_str(), to.as_str()),
+                ProfileAction::Delete { name } => profiles::delete(name.as_str()),
+            };
+
+            if let Err(e) = result {
+                output::error(e);
+                output::exit(1);
+            }
+        }
+
+        Some(Commands::Repair { infer, strip }) => {
+            let SavefileInfo { unlocks: mut file_unlocks, .. } = match read_savefile() {
+                Some(val) => val,
+                None => output::exit(1)
+            };
+
+            let mode = match (infer, strip) {
+                (true, _) => RepairMode::Infer,
+                (_, true) => RepairMode::Strip,
+                _ => {
+                    if !print_contradictions(&file_unlocks) {
+                        output::line("No contradictions found.");
+                    }
+                    return;
+                }
+            };
+
+            let fixes = file_unlocks.repair(mode);
+            print_fixes(&fixes, mode);
+            if !fixes.is_empty() {
+                save_to_savefile(&file_unlocks);
+            }
+        }
+
+        Some(Commands::Check) => {
+            if !check_savefile() {
+                output::exit(1);
+            }
+        }
+
         None => {
-            let SavefileInfo { unlocks, created_new_file } = match read_savefile() {
+            let SavefileInfo { mut unlocks, created_new_file } = match read_savefile() {
                 Some(val) => val,
                 None => return
             };
-            get_random_pick(&unlocks);
-            if created_new_file {
+            if cli.players > 1 {
+                let pick = get_random_coop_pick(&unlocks, cli.players as usize, cli.seed, cli.explain);
+                if let Some(pick) = pick.filter(|_| cli.route) {
+                    print_route(pick.targets());
+                }
+                if created_new_file {
+                    save_to_savefile(&unlocks);
+                }
+                return;
+            }
+            let pick = get_random_pick(&unlocks, cli.seed, &Exclusion::default(), cli.explain);
+            let rolled = pick.is_some();
+            if let Some((ch, targs)) = pick {
+                if cli.route {
+                    print_route(&targs);
+                }
+                unlocks.record_roll(ch, targs);
+            }
+            if rolled || created_new_file {
                 save_to_savefile(&unlocks);
             }
         }
@@ -0,0 +1,458 @@
+use crate::randomizer::challenges::Challenge;
+use crate::randomizer::characters::Character;
+use crate::randomizer::consistency::{Contradiction, Fix};
+use crate::randomizer::dependency::DependencyValue;
+use crate::randomizer::history::RollRecord;
+use crate::randomizer::planner::Run;
+use crate::randomizer::route::Route;
+use crate::randomizer::targets::Target;
+use crate::toml_parse::ruleset::RulesetFile;
+use crate::toml_parse::savefile::Problem;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Mutex, OnceLock};
+
+// format picked with --output, the structured formats collect everything the command would print
+// into a report that is printed once the command is done
+static FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+static REPORT: Mutex<Report> = Mutex::new(Report::new());
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    Json,
+    Toml,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OutputFormat::Text => write!(f, "text"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Toml => write!(f, "toml"),
+        }
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "toml" => Ok(OutputFormat::Toml),
+            _ => Err("Could not convert string to OutputFormat"),
+        }
+    }
+}
+
+// the schema of the structured output, the same for every command: the lists are always there,
+// the other fields only when the command has them. Values come before tables so it also works
+// as TOML
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub command: String,
+    pub ok: bool,
+    // lines the text output would print that aren't part of the fields below
+    pub messages: Vec<String>,
+    pub errors: Vec<String>,
+    // only with --explain
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub why: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerolls_left: Option<usize>,
+    // in the format picked with `graph --format`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub graph: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roll: Option<RollReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenge: Option<ChallengeReport>,
+    // only with --route
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub route: Option<RouteReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<StatusReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub history: Option<Vec<HistoryReport>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub challenges: Option<Vec<ChallengeStatus>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan: Option<PlanReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problems: Option<Vec<ProblemReport>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contradictions: Option<Vec<ContradictionReport>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fixes: Option<Vec<FixReport>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rules: Option<RulesetFile>,
+    pub skipped: Vec<SkippedReport>,
+}
+
+impl Report {
+    const fn new() -> Self {
+        Report {
+            command: String::new(),
+            ok: true,
+            messages: Vec::new(),
+            errors: Vec::new(),
+            why: Vec::new(),
+            rerolls_left: None,
+            graph: None,
+            roll: None,
+            challenge: None,
+            route: None,
+            status: None,
+            history: None,
+            challenges: None,
+            plan: None,
+            problems: None,
+            contradictions: None,
+            fixes: None,
+            rules: None,
+            skipped: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct RollReport {
+    // one for each player
+    pub characters: Vec<String>,
+    pub targets: Vec<String>,
+    pub hard: bool,
+}
+
+impl RollReport {
+    pub fn new(characters: &[Character], targets: &HashSet<Target>, hard: bool) -> Self {
+        RollReport {
+            characters: characters.iter().map(|ch| ch.to_string()).collect(),
+            targets: sorted_names(targets),
+            hard,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChallengeReport {
+    pub number: u32,
+    pub name: String,
+    pub goal: String,
+}
+
+impl From<Challenge> for ChallengeReport {
+    fn from(challenge: Challenge) -> Self {
+        ChallengeReport {
+            number: challenge.number(),
+            name: challenge.to_string(),
+            goal: challenge.goal().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ChallengeStatus {
+    pub number: u32,
+    pub name: String,
+    pub goal: String,
+    // done, todo or locked
+    pub state: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RouteReport {
+    pub stops: Vec<StopReport>,
+    pub items: Vec<ItemReport>,
+    pub time_limits: Vec<TimeLimitReport>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StopReport {
+    pub floor: String,
+    pub bosses: Vec<String>,
+    pub notes: Vec<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ItemReport {
+    pub name: String,
+    pub found_at: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct TimeLimitReport {
+    pub target: String,
+    pub limit: String,
+}
+
+impl From<&Route> for RouteReport {
+    fn from(route: &Route) -> Self {
+        RouteReport {
+            stops: route
+                .stops()
+                .iter()
+                .map(|stop| StopReport {
+                    floor: stop.floor().to_string(),
+                    bosses: stop.bosses().iter().map(|boss| boss.to_string()).collect(),
+                    notes: stop.notes().iter().map(|note| note.to_string()).collect(),
+                })
+                .collect(),
+            items: route
+                .items()
+                .iter()
+                .map(|item| ItemReport {
+                    name: item.to_string(),
+                    found_at: item.found_at().to_string(),
+                })
+                .collect(),
+            time_limits: route
+                .time_limits()
+                .iter()
+                .map(|(targ, limit)| TimeLimitReport {
+                    target: targ.to_string(),
+                    limit: limit.to_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct StatusReport {
+    pub total_marks: usize,
+    pub max_marks: usize,
+    pub locked_characters: Vec<String>,
+    pub locked_targets: Vec<String>,
+    pub missing_unlocks: Vec<String>,
+    pub characters: Vec<CharacterStatus>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct CharacterStatus {
+    pub name: String,
+    pub unlocked: bool,
+    pub marks: Vec<MarkStatus>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MarkStatus {
+    pub target: String,
+    // hard, normal, todo or locked
+    pub state: &'static str,
+}
+
+#[derive(Serialize, Debug)]
+pub struct HistoryReport {
+    pub timestamp: u64,
+    pub outcome: String,
+    pub character: String,
+    pub targets: Vec<String>,
+}
+
+impl From<&RollRecord> for HistoryReport {
+    fn from(record: &RollRecord) -> Self {
+        HistoryReport {
+            timestamp: record.timestamp(),
+            outcome: record.outcome().to_string(),
+            character: record.character().to_string(),
+            targets: sorted_names(record.targets()),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct PlanReport {
+    pub goal: String,
+    // empty when the goal is already unlocked
+    pub runs: Vec<RunReport>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct RunReport {
+    pub character: String,
+    pub target: String,
+    pub unlocks: Vec<String>,
+}
+
+impl From<&Run> for RunReport {
+    fn from(run: &Run) -> Self {
+        RunReport {
+            character: run.character().to_string(),
+            target: run.target().to_string(),
+            unlocks: run.unlocks().clone(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ProblemReport {
+    // error or warning
+    pub severity: String,
+    pub location: String,
+    pub message: String,
+}
+
+impl From<&Problem> for ProblemReport {
+    fn from(problem: &Problem) -> Self {
+        ProblemReport {
+            severity: problem.severity().to_string(),
+            location: problem.location().to_string(),
+            message: problem.message().to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct ContradictionReport {
+    // the unlock or the marks
+    pub value: String,
+    // what `value` needs that isn't unlocked
+    pub missing: String,
+}
+
+impl From<&Contradiction> for ContradictionReport {
+    fn from(contradiction: &Contradiction) -> Self {
+        match contradiction {
+            Contradiction::UnmetDependency { value, missing } => ContradictionReport {
+                value: value.to_string(),
+                missing: missing.to_string(),
+            },
+            Contradiction::MarksOnLockedCharacter(ch) => ContradictionReport {
+                value: format!("marks of {}", ch),
+                missing: DependencyValue::Character(*ch).to_string(),
+            },
+            Contradiction::MarkOnLockedTarget { character, target } => ContradictionReport {
+                value: format!("{} mark of {}", target, character),
+                missing: DependencyValue::Target(*target).to_string(),
+            },
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct FixReport {
+    // unlocked, locked or unmarked
+    pub change: &'static str,
+    pub value: String,
+}
+
+impl From<&Fix> for FixReport {
+    fn from(fix: &Fix) -> Self {
+        match fix {
+            Fix::Unlocked(val) => FixReport {
+                change: "unlocked",
+                value: val.to_string(),
+            },
+            Fix::Locked(val) => FixReport {
+                change: "locked",
+                value: val.to_string(),
+            },
+            Fix::RemovedMarks(ch) => FixReport {
+                change: "unmarked",
+                value: format!("every mark of {}", ch),
+            },
+            Fix::RemovedMark { character, target } => FixReport {
+                change: "unmarked",
+                value: format!("{} mark of {}", target, character),
+            },
+        }
+    }
+}
+
+// an argument the command didn't use
+#[derive(Serialize, Debug)]
+pub struct SkippedReport {
+    pub item: String,
+    pub reason: String,
+}
+
+fn sorted_names(targets: &HashSet<Target>) -> Vec<String> {
+    let mut targs: Vec<&Target> = targets.iter().collect();
+    targs.sort();
+    targs.iter().map(|targ| targ.to_string()).collect()
+}
+
+pub fn init(format: OutputFormat, command: &str) {
+    FORMAT.get_or_init(|| format);
+    report(|report| report.command = command.to_string());
+}
+
+pub fn is_text() -> bool {
+    FORMAT.get().copied().unwrap_or_default() == OutputFormat::Text
+}
+
+// changes the report, the text output has none so it is thrown away
+pub fn report(f: impl FnOnce(&mut Report)) {
+    if !is_text() {
+        f(&mut REPORT.lock().unwrap_or_else(|e| e.into_inner()));
+    }
+}
+
+pub fn line(msg: impl fmt::Display) {
+    if is_text() {
+        println!("{}", msg);
+        return;
+    }
+
+    let msg = msg.to_string();
+    let msg = msg.trim();
+    if !msg.is_empty() {
+        report(|report| report.messages.push(msg.to_string()));
+    }
+}
+
+pub fn error(msg: impl fmt::Display) {
+    if is_text() {
+        eprintln!("{}", msg);
+        return;
+    }
+
+    report(|report| {
+        report.ok = false;
+        report.errors.push(msg.to_string());
+    });
+}
+
+// `action` is what would have been done with `item`, like "unlocking Cain"
+pub fn skipped(item: &str, action: String, reason: &str) {
+    if is_text() {
+        println!("Skipping {}: {}.", action, reason);
+        return;
+    }
+
+    report(|report| {
+        report.skipped.push(SkippedReport {
+            item: item.to_string(),
+            reason: reason.to_string(),
+        })
+    });
+}
+
+// prints the report of a structured format
+pub fn finish() {
+    let format = FORMAT.get().copied().unwrap_or_default();
+    let report = REPORT.lock().unwrap_or_else(|e| e.into_inner());
+    let contents = match format {
+        OutputFormat::Text => return,
+        OutputFormat::Json => serde_json::to_string_pretty(&*report).map_err(|e| e.to_string()),
+        OutputFormat::Toml => toml::to_string(&*report).map_err(|e| e.to_string()),
+    };
+
+    match contents {
+        Ok(contents) => println!("{}", contents.trim_end()),
+        Err(e) => eprintln!("couldn't write the {} output: {}", format, e),
+    }
+}
+
+pub fn exit(code: i32) -> ! {
+    if code != 0 {
+        report(|report| report.ok = false);
+    }
+    finish();
+    std::process::exit(code)
+}
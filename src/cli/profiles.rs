@@ -0,0 +1,152 @@
+use crate::randomizer::pool::Unlocks;
+use crate::toml_parse::savefile::{self, Savefile};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+// profile picked with --profile, savefiles are read from and written to it for the whole command
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    NoConfigDirError,
+    InvalidNameError(String),
+    NotFoundError(String),
+    AlreadyExistsError(String),
+    IoError(std::io::Error),
+    SavefileError(savefile::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Error::*;
+        match self {
+            NoConfigDirError => write!(f, "neither XDG_CONFIG_HOME nor HOME is set"),
+            InvalidNameError(name) => write!(
+                f,
+                "invalid profile name {}, use only letters, numbers, - and _",
+                name
+            ),
+            NotFoundError(name) => write!(
+                f,
+                "no such profile: {}, create it with `srati profile create {}`",
+                name, name
+            ),
+            AlreadyExistsError(name) => write!(f, "profile {} already exists", name),
+            IoError(e) => write!(f, "{}", e),
+            SavefileError(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+impl From<savefile::Error> for Error {
+    fn from(e: savefile::Error) -> Self {
+        Error::SavefileError(e)
+    }
+}
+
+pub fn set_active(name: &str) -> Result<(), Error> {
+    let path = profile_path(name)?;
+    if !path.exists() {
+        return Err(Error::NotFoundError(name.to_string()));
+    }
+    ACTIVE_PROFILE.get_or_init(|| name.to_string());
+    Ok(())
+}
+
+pub fn active_path() -> Option<PathBuf> {
+    ACTIVE_PROFILE
+        .get()
+        .and_then(|name| profile_path(name).ok())
+}
+
+// $XDG_CONFIG_HOME/srati/profiles, falling back to ~/.config like the XDG spec says
+fn profiles_dir() -> Result<PathBuf, Error> {
+    let config_dir = match env::var("XDG_CONFIG_HOME") {
+        Ok(dir) if PathBuf::from(&dir).is_absolute() => PathBuf::from(dir),
+        _ => match env::var("HOME") {
+            Ok(home) => PathBuf::from(home).join(".config"),
+            Err(_) => return Err(Error::NoConfigDirError),
+        },
+    };
+
+    Ok(config_dir.join("srati").join("profiles"))
+}
+
+fn profile_path(name: &str) -> Result<PathBuf, Error> {
+    let is_valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !is_valid {
+        return Err(Error::InvalidNameError(name.to_string()));
+    }
+
+    Ok(profiles_dir()?.join(format!("{}.toml", name)))
+}
+
+pub fn list() -> Result<Vec<String>, Error> {
+    let dir = profiles_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut names: Vec<String> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| path.file_stem()?.to_str().map(|stem| stem.to_string()))
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+pub fn create(name: &str) -> Result<(), Error> {
+    let path = new_profile_path(name)?;
+    let savefile: Savefile = Into::into(&Unlocks::default());
+    savefile.write_to_file(path.to_string_lossy().to_string())?;
+
+    Ok(())
+}
+
+pub fn copy(from: &str, to: &str) -> Result<(), Error> {
+    let from_path = profile_path(from)?;
+    if !from_path.exists() {
+        return Err(Error::NotFoundError(from.to_string()));
+    }
+    let to_path = new_profile_path(to)?;
+    fs::copy(from_path, to_path)?;
+
+    Ok(())
+}
+
+pub fn delete(name: &str) -> Result<(), Error> {
+    let path = profile_path(name)?;
+    if !path.exists() {
+        return Err(Error::NotFoundError(name.to_string()));
+    }
+    fs::remove_file(path)?;
+
+    Ok(())
+}
+
+// path of a profile that doesn't exist yet, with the profiles directory created
+fn new_profile_path(name: &str) -> Result<PathBuf, Error> {
+    let path = profile_path(name)?;
+    if path.exists() {
+        return Err(Error::AlreadyExistsError(name.to_string()));
+    }
+    fs::create_dir_all(profiles_dir()?)?;
+
+    Ok(path)
+}
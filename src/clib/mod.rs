@@ -1,10 +1,17 @@
+// every export null-checks its pointers with check_null! before dereferencing them
+#![allow(clippy::not_unsafe_ptr_arg_deref)]
+
 use crate::randomizer::characters::Character;
+use crate::randomizer::coop::CoopPick;
+use crate::randomizer::difficulty::Difficulty;
 use crate::randomizer::pool::Unlocks;
 use crate::randomizer::targets::Target;
 use crate::toml_parse::savefile::{Error as SfError, Savefile as SfSavefile};
+use std::collections::HashSet;
 use std::ffi::{CStr, CString};
 use std::fmt;
 use std::os::raw::{c_char, c_int, c_void};
+use strum::IntoEnumIterator;
 
 #[repr(C)]
 pub enum Error {
@@ -16,6 +23,7 @@ pub enum Error {
     FfiError,
     NullPtrError,
     CouldNotRollError,
+    InvalidPlayersError,
 }
 
 macro_rules! check_null(
@@ -35,10 +43,14 @@ macro_rules! check_null(
 impl Error {
     fn to_c_int(sf_err: SfError) -> c_int {
         match sf_err {
-            SfError::SerializeError(_) => return Error::SerializationError as c_int,
-            SfError::DeserializeError(_) => return Error::DeserializationError as c_int,
-            SfError::ParseError => return Error::ParseError as c_int,
-            SfError::IoError(_) => return Error::IoError as c_int,
+            SfError::SerializeError(_) => Error::SerializationError as c_int,
+            SfError::DeserializeError(_) | SfError::UnsupportedVersionError(_) => {
+                Error::DeserializationError as c_int
+            }
+            SfError::ParseError | SfError::RulesetError(_) | SfError::ValidationError(_) => {
+                Error::ParseError as c_int
+            }
+            SfError::IoError(_) => Error::IoError as c_int,
         }
     }
 }
@@ -96,11 +108,110 @@ pub struct RunTarget {
 pub extern "C" fn randomize(unlocks_handle: UnlocksHandle, targets_out: *mut RunTarget) -> c_int {
     check_null!(unlocks_handle, targets_out);
 
+    randomize_with(unlocks_handle, targets_out, |unlocks| unlocks.get_random_pick())
+}
+
+#[no_mangle]
+pub extern "C" fn randomize_seeded(
+    unlocks_handle: UnlocksHandle,
+    seed: u64,
+    targets_out: *mut RunTarget,
+) -> c_int {
+    check_null!(unlocks_handle, targets_out);
+
+    randomize_with(unlocks_handle, targets_out, |unlocks| {
+        unlocks.get_seeded_random_pick(seed)
+    })
+}
+
+// the most players the game has controller slots for
+const MAX_PLAYERS: usize = 4;
+
+#[repr(C)]
+pub struct CoopRunTarget {
+    characters: [Character; MAX_PLAYERS],
+    character_count: c_int,
+    targets: c_int,
+}
+
+#[no_mangle]
+pub extern "C" fn randomize_coop(
+    unlocks_handle: UnlocksHandle,
+    players: c_int,
+    targets_out: *mut CoopRunTarget,
+) -> c_int {
+    check_null!(unlocks_handle, targets_out);
+
+    randomize_coop_with(unlocks_handle, players, targets_out, |unlocks, players| {
+        unlocks.get_coop_pick(players)
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn randomize_coop_seeded(
+    unlocks_handle: UnlocksHandle,
+    players: c_int,
+    seed: u64,
+    targets_out: *mut CoopRunTarget,
+) -> c_int {
+    check_null!(unlocks_handle, targets_out);
+
+    randomize_coop_with(unlocks_handle, players, targets_out, |unlocks, players| {
+        unlocks.get_seeded_coop_pick(players, seed)
+    })
+}
+
+fn randomize_coop_with(
+    unlocks_handle: UnlocksHandle,
+    players: c_int,
+    targets_out: *mut CoopRunTarget,
+    roll: impl FnOnce(&Unlocks, usize) -> Option<CoopPick>,
+) -> c_int {
+    let players = match usize::try_from(players) {
+        Ok(p) if (1..=MAX_PLAYERS).contains(&p) => p,
+        _ => return Error::InvalidPlayersError as c_int,
+    };
+
+    let unlocks = unsafe { Box::<Unlocks>::from_raw(unlocks_handle.cast()) };
+
+    let pick = match roll(&unlocks, players) {
+        Some(p) => p,
+        None => {
+            Box::leak(unlocks);
+            return Error::CouldNotRollError as c_int;
+        }
+    };
+
+    let targets = unsafe { &mut *targets_out };
+    // unused slots repeat the first character so the array never holds garbage
+    targets.characters = [pick.characters()[0]; MAX_PLAYERS];
+    for (slot, ch) in pick.characters().iter().enumerate() {
+        targets.characters[slot] = *ch;
+    }
+    targets.character_count = pick.characters().len() as c_int;
+    targets.targets = 0;
+
+    for target in pick.targets() {
+        targets.targets |= 1 << (*target as u32);
+    }
+
+    Box::leak(unlocks);
+    0
+}
+
+fn randomize_with(
+    unlocks_handle: UnlocksHandle,
+    targets_out: *mut RunTarget,
+    roll: impl FnOnce(&Unlocks) -> Option<(Character, HashSet<Target>)>,
+) -> c_int {
     let unlocks = unsafe { Box::<Unlocks>::from_raw(unlocks_handle.cast()) };
 
-    let pick = match unlocks.get_random_pick() {
+    let pick = match roll(&unlocks) {
         Some(p) => p,
-        None => return Error::CouldNotRollError as c_int,
+        None => {
+            Box::leak(unlocks);
+            return Error::CouldNotRollError as c_int;
+        }
     };
 
     let targets = unsafe { &mut *targets_out };
@@ -115,6 +226,36 @@ pub extern "C" fn randomize(unlocks_handle: UnlocksHandle, targets_out: *mut Run
     0
 }
 
+// targets is a bitmask like the one of RunTarget, the marks passed on the way are added too
+// unless set_imply_marks turned it off
+#[no_mangle]
+pub extern "C" fn add_marks(
+    unlocks_handle: UnlocksHandle,
+    character: Character,
+    targets: c_int,
+    difficulty: Difficulty,
+) -> c_int {
+    check_null!(unlocks_handle);
+
+    let unlocks = unsafe { &mut *unlocks_handle.cast::<Unlocks>() };
+    let marks = Target::iter()
+        .filter(|targ| targets & (1 << (*targ as u32)) != 0)
+        .collect();
+    unlocks.add_marks(character, marks, difficulty);
+
+    0
+}
+
+#[no_mangle]
+pub extern "C" fn set_imply_marks(unlocks_handle: UnlocksHandle, is_enabled: bool) -> c_int {
+    check_null!(unlocks_handle);
+
+    let unlocks = unsafe { &mut *unlocks_handle.cast::<Unlocks>() };
+    unlocks.set_imply_marks(is_enabled);
+
+    0
+}
+
 fn print_displayable(ds: &impl fmt::Display) -> Result<RustString, Error> {
     let c_char = match CString::new(ds.to_string()) {
         Err(_) => return Err(Error::FfiError),
@@ -0,0 +1,259 @@
+// Reader for the game's own persistentgamedata*.dat (Repentance)
+//
+// The file starts with a 16 byte header followed by chunks, every chunk is
+// [u32 type][u32 size][size bytes of data], all integers little endian.
+// Only two chunks matter to srati:
+//   achievements     - [u32 count] then one byte per achievement id, non zero when unlocked
+//   completion marks - [u32 count] then MARKS_PER_CHARACTER bytes per character (in Character
+//                      order), 0 for no mark, 1 for normal and 2 for hard
+// every other chunk is skipped by its size. Sample files live in fixtures/.
+
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::characters::Character;
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::pool::Unlocks;
+use crate::randomizer::targets::Target;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use strum::IntoEnumIterator;
+
+const HEADER: &[u8; 16] = b"ISAACNGSAVE09R  ";
+
+const ACHIEVEMENTS_CHUNK: u32 = 1;
+const COMPLETION_MARKS_CHUNK: u32 = 12;
+
+// order of the marks on a character's completion note, None for the ones srati doesn't track
+const MARK_ORDER: [Option<Target>; 13] = [
+    Some(Target::Heart),
+    Some(Target::Isaac),
+    Some(Target::Satan),
+    Some(Target::BossRush),
+    Some(Target::BlueBaby),
+    Some(Target::Lamb),
+    Some(Target::MegaSatan),
+    Some(Target::UltraGreed),
+    Some(Target::Greedier),
+    Some(Target::Hush),
+    Some(Target::Delirium),
+    Some(Target::Mother),
+    Some(Target::Beast),
+];
+const MARKS_PER_CHARACTER: usize = MARK_ORDER.len();
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    IoError(std::io::Error),
+    HeaderError,
+    EofError,
+    MissingChunkError(u32),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::IoError(e) => write!(f, "could not read game data: {}", e),
+            Error::HeaderError => write!(f, "not a Repentance persistentgamedata file"),
+            Error::EofError => write!(f, "game data ends in the middle of a chunk"),
+            Error::MissingChunkError(chunk) => write!(f, "game data has no chunk {}", chunk),
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+#[derive(Debug)]
+pub struct GameData {
+    achievements: HashSet<u16>,
+    marks: HashMap<Character, HashSet<(Target, Difficulty)>>,
+}
+
+impl GameData {
+    pub fn read_from_file(path: String) -> Result<Self, Error> {
+        let bytes = fs::read(path)?;
+        Self::parse(&bytes)
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER.len() || &bytes[..HEADER.len()] != HEADER {
+            return Err(Error::HeaderError);
+        }
+
+        let mut achievements = None;
+        let mut marks = None;
+
+        let mut offset = HEADER.len();
+        while offset < bytes.len() {
+            let chunk_type = read_u32(bytes, offset)?;
+            let size = read_u32(bytes, offset + 4)? as usize;
+            let data = bytes
+                .get(offset + 8..offset + 8 + size)
+                .ok_or(Error::EofError)?;
+
+            match chunk_type {
+                ACHIEVEMENTS_CHUNK => achievements = Some(Self::parse_achievements(data)?),
+                COMPLETION_MARKS_CHUNK => marks = Some(Self::parse_marks(data)?),
+                _ => {}
+            }
+
+            offset += 8 + size;
+        }
+
+        Ok(Self {
+            achievements: achievements.ok_or(Error::MissingChunkError(ACHIEVEMENTS_CHUNK))?,
+            marks: marks.ok_or(Error::MissingChunkError(COMPLETION_MARKS_CHUNK))?,
+        })
+    }
+
+    fn parse_achievements(data: &[u8]) -> Result<HashSet<u16>, Error> {
+        let count = read_u32(data, 0)? as usize;
+        let flags = data.get(4..4 + count).ok_or(Error::EofError)?;
+
+        Ok(flags
+            .iter()
+            .enumerate()
+            .filter(|(_, &flag)| flag != 0)
+            .map(|(id, _)| id as u16)
+            .collect())
+    }
+
+    fn parse_marks(
+        data: &[u8],
+    ) -> Result<HashMap<Character, HashSet<(Target, Difficulty)>>, Error> {
+        let count = read_u32(data, 0)? as usize;
+        let mut marks = HashMap::new();
+
+        for (i, ch) in Character::iter().take(count).enumerate() {
+            let start = 4 + i * MARKS_PER_CHARACTER;
+            let slots = data
+                .get(start..start + MARKS_PER_CHARACTER)
+                .ok_or(Error::EofError)?;
+
+            let completed: HashSet<(Target, Difficulty)> = slots
+                .iter()
+                .zip(MARK_ORDER.iter())
+                .filter(|(&slot, _)| slot != 0)
+                .filter_map(|(&slot, targ)| {
+                    let difficulty = if slot >= 2 {
+                        Difficulty::Hard
+                    } else {
+                        Difficulty::Normal
+                    };
+                    targ.map(|targ| (targ, difficulty))
+                })
+                .collect();
+
+            if !completed.is_empty() {
+                marks.insert(ch, completed);
+            }
+        }
+
+        Ok(marks)
+    }
+
+    pub fn is_achievement_unlocked(&self, id: u16) -> bool {
+        self.achievements.contains(&id)
+    }
+}
+
+impl From<&GameData> for Unlocks {
+    fn from(data: &GameData) -> Self {
+        let mut unl = Unlocks::default();
+
+        unl.add_unlocked_chars(
+            Character::iter()
+                .filter(|ch| ch.achievement_id().is_some_and(|id| data.is_achievement_unlocked(id)))
+                .collect(),
+        );
+
+        for (ch, marks) in &data.marks {
+            unl.set_marks(*ch, marks.clone());
+        }
+
+        // tainted characters are found in the closet at Home, so having one means Home was reached
+        if unl.unlocked_chars().iter().any(|ch| ch.is_tainted()) {
+            unl.add_unlocked_targets(HashSet::from([Target::Home]));
+        }
+
+        for achievement in Achievement::iter() {
+            unl.set_achievement_unlocked(
+                achievement,
+                data.is_achievement_unlocked(achievement.id()),
+            );
+        }
+
+        unl
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> Result<u32, Error> {
+    let le_bytes = bytes.get(offset..offset + 4).ok_or(Error::EofError)?;
+    Ok(u32::from_le_bytes(le_bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn read_fixture() -> GameData {
+        GameData::read_from_file(fixture("persistentgamedata1.dat")).unwrap()
+    }
+
+    #[test]
+    fn reads_achievements() {
+        let data = read_fixture();
+
+        assert!(data.is_achievement_unlocked(1));
+        assert!(data.is_achievement_unlocked(32));
+        assert!(data.is_achievement_unlocked(Achievement::ItLives.id()));
+        assert!(data.is_achievement_unlocked(Achievement::Polaroid.id()));
+        assert!(!data.is_achievement_unlocked(Achievement::Negative.id()));
+        assert!(!data.is_achievement_unlocked(42));
+        assert!(data.is_achievement_unlocked(100));
+    }
+
+    #[test]
+    fn reads_marks_with_difficulty() {
+        let data = read_fixture();
+
+        let isaac = &data.marks[&Character::Isaac];
+        assert!(isaac.contains(&(Target::Heart, Difficulty::Normal)));
+        assert!(isaac.contains(&(Target::Isaac, Difficulty::Normal)));
+        assert!(isaac.contains(&(Target::Satan, Difficulty::Hard)));
+        assert!(!isaac.contains(&(Target::Heart, Difficulty::Hard)));
+
+        let cain = &data.marks[&Character::Cain];
+        assert_eq!(cain.len(), 2);
+        assert!(cain.contains(&(Target::BossRush, Difficulty::Normal)));
+
+        assert!(!data.marks.contains_key(&Character::Magdalene));
+    }
+
+    #[test]
+    fn converts_to_unlocks() {
+        let unlocks = Unlocks::from(&read_fixture());
+
+        assert!(unlocks.unlocked_chars().contains(&Character::BlueBaby));
+        assert!(!unlocks.unlocked_chars().contains(&Character::Eve));
+        assert!(unlocks.has_mark(&Character::Isaac, Target::Satan, Difficulty::Hard));
+        assert!(unlocks.is_achievement_unlocked(Achievement::Polaroid));
+        assert!(unlocks.is_achievement_unlocked(Achievement::from_id(100).unwrap()));
+        assert!(!unlocks.is_achievement_unlocked(Achievement::from_id(101).unwrap()));
+    }
+
+    #[test]
+    fn rejects_bad_header() {
+        let result = GameData::read_from_file(fixture("persistentgamedata_bad_header.dat"));
+
+        assert!(matches!(result, Err(Error::HeaderError)));
+    }
+}
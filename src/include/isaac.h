@@ -1,3 +1,4 @@
+#include <stdbool.h>
 #include <stdint.h>
 
 enum Error {
@@ -9,6 +10,7 @@ enum Error {
   FFI_ERR,
   NULLPTR_ERR,
   COULD_NOT_ROLL_ERR,
+  INVALID_PLAYERS_ERR,
 };
 
 enum Character {
@@ -64,9 +66,17 @@ enum Target {
   HEART_TARG,
 
   MOM_TARG,
+
+  GREEDIER_TARG,
+  HOME_TARG,
   TARGET_COUNT,
 };
 
+enum Difficulty {
+  NORMAL_DIFF = 0,
+  HARD_DIFF,
+};
+
 typedef void *UnlocksHandle;
 typedef char *RustString;
 
@@ -75,6 +85,14 @@ typedef struct {
   int targets;
 } RunTarget;
 
+#define MAX_PLAYERS 4
+
+typedef struct {
+  enum Character characters[MAX_PLAYERS];
+  int character_count;
+  int targets;
+} CoopRunTarget;
+
 #ifdef __cplusplus
 extern "C" {
 #endif // __cplusplus
@@ -87,6 +105,16 @@ int print_target(enum Target target, RustString *str);
 int free_string(RustString str);
 
 int randomize(const UnlocksHandle unlocks_handle, RunTarget *targets_out);
+int randomize_seeded(const UnlocksHandle unlocks_handle, uint64_t seed,
+                     RunTarget *targets_out);
+int add_marks(UnlocksHandle unlocks_handle, enum Character character,
+              int targets, enum Difficulty difficulty);
+int set_imply_marks(UnlocksHandle unlocks_handle, bool is_enabled);
+
+int randomize_coop(const UnlocksHandle unlocks_handle, int players,
+                   CoopRunTarget *targets_out);
+int randomize_coop_seeded(const UnlocksHandle unlocks_handle, int players,
+                          uint64_t seed, CoopRunTarget *targets_out);
 
 #ifdef __cplusplus
 } // extern "C"
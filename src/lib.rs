@@ -1,4 +1,4 @@
 pub mod clib;
 
-mod randomizer;
-mod toml_parse;
+pub mod randomizer;
+pub mod toml_parse;
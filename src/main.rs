@@ -1,6 +1,7 @@
 mod cli;
-mod randomizer;
-mod toml_parse;
+mod dat_parse;
+
+use isaac::{randomizer, toml_parse};
 
 fn main() {
     cli::parse_cmd();
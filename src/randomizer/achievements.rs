@@ -0,0 +1,190 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
+use crate::randomizer::targets::Target;
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+// Repentance numbers its achievements from 1 to this
+pub const LAST_ACHIEVEMENT_ID: u16 = 637;
+
+// the game's achievements, character unlocks are tracked as characters. The ones that decide where
+// a run can go have their own variant, every other one is Other with its id, made through from_id
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Achievement {
+    ItLives,
+    Polaroid,
+    Negative,
+    HolyMantle,
+    BlueWomb,
+    TheVoid,
+    ASecretExit,
+    AStrangeDoor,
+    Other(u16),
+}
+
+impl Achievement {
+    // the achievements the rules use
+    pub const TRACKED: [Achievement; 8] = [
+        Achievement::ItLives,
+        Achievement::Polaroid,
+        Achievement::Negative,
+        Achievement::HolyMantle,
+        Achievement::BlueWomb,
+        Achievement::TheVoid,
+        Achievement::ASecretExit,
+        Achievement::AStrangeDoor,
+    ];
+
+    // id in the game's achievement list (and the achievements chunk of persistentgamedata)
+    pub fn id(&self) -> u16 {
+        use Achievement::*;
+        match self {
+            ItLives => 4,
+            Polaroid => 43,
+            Negative => 78,
+            HolyMantle => 167,
+            BlueWomb => 234,
+            TheVoid => 320,
+            ASecretExit => 407,
+            AStrangeDoor => 635,
+            Other(id) => *id,
+        }
+    }
+
+    // None for ids outside the game's list and for character unlocks
+    pub fn from_id(id: u16) -> Option<Self> {
+        if !(1..=LAST_ACHIEVEMENT_ID).contains(&id)
+            || Character::iter().any(|ch| ch.achievement_id() == Some(id))
+        {
+            return None;
+        }
+
+        Some(
+            Self::TRACKED
+                .into_iter()
+                .find(|achievement| achievement.id() == id)
+                .unwrap_or(Achievement::Other(id)),
+        )
+    }
+
+    // the boss that has to be beaten for the achievement, None for the ones without rules
+    pub fn unlocked_by(&self) -> Option<Target> {
+        use Achievement::*;
+        match self {
+            ItLives | BlueWomb => Some(Target::Heart),
+            Polaroid => Some(Target::Isaac),
+            Negative => Some(Target::Satan),
+            HolyMantle => Some(Target::UltraGreed),
+            TheVoid | ASecretExit => Some(Target::Hush),
+            AStrangeDoor => Some(Target::Mother),
+            Other(_) => None,
+        }
+    }
+}
+
+// every achievement in the order of the game's list
+impl IntoEnumIterator for Achievement {
+    type Iterator = std::vec::IntoIter<Achievement>;
+
+    fn iter() -> Self::Iterator {
+        (1..=LAST_ACHIEVEMENT_ID)
+            .filter_map(Achievement::from_id)
+            .collect::<Vec<Achievement>>()
+            .into_iter()
+    }
+}
+
+impl HasDependency for Achievement {
+    fn depends_on(&self) -> Dependency {
+        match self.unlocked_by() {
+            Some(targ) => Dependency::Singular(DependencyValue::Target(targ)),
+            None => Dependency::None,
+        }
+    }
+}
+
+impl fmt::Display for Achievement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Achievement::*;
+        match self {
+            ItLives => write!(f, "It Lives"),
+            Polaroid => write!(f, "Polaroid"),
+            Negative => write!(f, "Negative"),
+            HolyMantle => write!(f, "Holy Mantle"),
+            BlueWomb => write!(f, "Blue Womb"),
+            TheVoid => write!(f, "The Void"),
+            ASecretExit => write!(f, "A Secret Exit"),
+            AStrangeDoor => write!(f, "A Strange Door"),
+            Other(id) => write!(f, "Achievement {}", id),
+        }
+    }
+}
+
+impl FromStr for Achievement {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Achievement::*;
+        match s {
+            "It Lives" => Ok(ItLives),
+            "Polaroid" => Ok(Polaroid),
+            "Negative" => Ok(Negative),
+            "Holy Mantle" => Ok(HolyMantle),
+            "Blue Womb" => Ok(BlueWomb),
+            "The Void" => Ok(TheVoid),
+            "A Secret Exit" => Ok(ASecretExit),
+            "A Strange Door" => Ok(AStrangeDoor),
+            _ => s
+                .strip_prefix("Achievement ")
+                .and_then(|id| id.parse::<u16>().ok())
+                .and_then(Achievement::from_id)
+                .filter(|achievement| matches!(achievement, Other(_)))
+                .ok_or("Could not convert string to Achievement"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_id_keeps_named_achievements() {
+        for achievement in Achievement::TRACKED {
+            assert_eq!(Achievement::from_id(achievement.id()), Some(achievement));
+        }
+        assert_eq!(Achievement::from_id(100), Some(Achievement::Other(100)));
+    }
+
+    #[test]
+    fn from_id_rejects_character_and_unknown_ids() {
+        let magdalene = Character::Magdalene.achievement_id().unwrap();
+
+        assert_eq!(Achievement::from_id(magdalene), None);
+        assert_eq!(Achievement::from_id(0), None);
+        assert_eq!(Achievement::from_id(LAST_ACHIEVEMENT_ID + 1), None);
+    }
+
+    #[test]
+    fn iter_covers_every_achievement_but_characters() {
+        let characters = Character::iter()
+            .filter(|ch| ch.achievement_id().is_some())
+            .count();
+
+        assert_eq!(
+            Achievement::iter().count(),
+            LAST_ACHIEVEMENT_ID as usize - characters
+        );
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for achievement in Achievement::iter() {
+            assert_eq!(achievement.to_string().parse(), Ok(achievement));
+        }
+        assert_eq!(Achievement::Other(100).to_string(), "Achievement 100");
+        assert!("Achievement 4".parse::<Achievement>().is_err());
+        assert!("Achievement 0".parse::<Achievement>().is_err());
+    }
+}
@@ -0,0 +1,199 @@
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
+use crate::randomizer::targets::Target;
+use std::fmt;
+use std::str::FromStr;
+use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
+
+// in the order of the challenge list, so the number shown in game is the index + 1
+#[derive(EnumIter, EnumCountMacro, Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Challenge {
+    PitchBlack,
+    HighBrow,
+    HeadTrauma,
+    DarknessFalls,
+    TheTank,
+    SolarSystem,
+    SuicideKing,
+    CatGotYourTongue,
+    DemoMan,
+    Cursed,
+    GlassCannon,
+    WhenLifeGivesYouLemons,
+    Beans,
+    ItsInTheCards,
+    SlowRoll,
+    ComputerSavvy,
+    WakaWaka,
+    TheHost,
+    TheFamilyMan,
+    Purist,
+    Xxxxxxxxl,
+    Speed,
+    BlueBomber,
+    PayToPlay,
+    HaveAHeart,
+    IRule,
+    Brains,
+    PrideDay,
+    OnansStreak,
+    TheGuardian,
+    Backasswards,
+    AprilsFool,
+    PokeyMans,
+    UltraHard,
+    Pong,
+    ScatMan,
+    BloodyMary,
+    BaptismByFire,
+    IsaacsAwakening,
+    SeeingDouble,
+    PicaRun,
+    HotPotato,
+    Cantripped,
+    RedRedemption,
+    DeleteThis,
+}
+
+impl Challenge {
+    pub fn number(&self) -> u32 {
+        *self as u32 + 1
+    }
+
+    // the boss that ends the challenge
+    pub fn goal(&self) -> Target {
+        use Challenge::*;
+        match self {
+            PitchBlack | HighBrow | HeadTrauma | TheTank | SolarSystem | SuicideKing
+            | CatGotYourTongue | Cursed | WhenLifeGivesYouLemons | TheHost | IRule
+            | BaptismByFire => Target::Heart,
+            DemoMan | GlassCannon | ItsInTheCards | ComputerSavvy | PayToPlay | HaveAHeart
+            | PrideDay | IsaacsAwakening => Target::Isaac,
+            DarknessFalls | Beans | SlowRoll | Purist | Xxxxxxxxl | AprilsFool | BloodyMary => {
+                Target::Satan
+            }
+            WakaWaka | BlueBomber | Brains => Target::BlueBaby,
+            TheFamilyMan | Speed | OnansStreak => Target::Lamb,
+            Backasswards | UltraHard => Target::MegaSatan,
+            TheGuardian | PokeyMans | Pong => Target::Hush,
+            ScatMan | SeeingDouble | PicaRun | HotPotato => Target::Mother,
+            Cantripped | RedRedemption | DeleteThis => Target::Beast,
+        }
+    }
+}
+
+// a challenge can only be started once the floor of its goal is open. This is an approximation,
+// in game most challenges are also unlocked by an achievement of their own that isn't modelled
+impl HasDependency for Challenge {
+    fn depends_on(&self) -> Dependency {
+        Dependency::Singular(DependencyValue::Target(self.goal()))
+    }
+}
+
+impl fmt::Display for Challenge {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Challenge::*;
+        match self {
+            PitchBlack => write!(f, "Pitch Black"),
+            HighBrow => write!(f, "High Brow"),
+            HeadTrauma => write!(f, "Head Trauma"),
+            DarknessFalls => write!(f, "Darkness Falls"),
+            TheTank => write!(f, "The Tank"),
+            SolarSystem => write!(f, "Solar System"),
+            SuicideKing => write!(f, "Suicide King"),
+            CatGotYourTongue => write!(f, "Cat Got Your Tongue"),
+            DemoMan => write!(f, "Demo Man"),
+            Cursed => write!(f, "Cursed!"),
+            GlassCannon => write!(f, "Glass Cannon"),
+            WhenLifeGivesYouLemons => write!(f, "When Life Gives You Lemons"),
+            Beans => write!(f, "Beans!"),
+            ItsInTheCards => write!(f, "It's In The Cards"),
+            SlowRoll => write!(f, "Slow Roll"),
+            ComputerSavvy => write!(f, "Computer Savvy"),
+            WakaWaka => write!(f, "Waka Waka"),
+            TheHost => write!(f, "The Host"),
+            TheFamilyMan => write!(f, "The Family Man"),
+            Purist => write!(f, "Purist"),
+            Xxxxxxxxl => write!(f, "XXXXXXXXL"),
+            Speed => write!(f, "SPEED!"),
+            BlueBomber => write!(f, "Blue Bomber"),
+            PayToPlay => write!(f, "PAY TO PLAY"),
+            HaveAHeart => write!(f, "Have a Heart"),
+            IRule => write!(f, "I RULE!"),
+            Brains => write!(f, "BRAINS!"),
+            PrideDay => write!(f, "PRIDE DAY!"),
+            OnansStreak => write!(f, "Onan's Streak"),
+            TheGuardian => write!(f, "The Guardian"),
+            Backasswards => write!(f, "Backasswards"),
+            AprilsFool => write!(f, "Aprils Fool"),
+            PokeyMans => write!(f, "Pokey Mans"),
+            UltraHard => write!(f, "Ultra Hard"),
+            Pong => write!(f, "Pong"),
+            ScatMan => write!(f, "Scat Man"),
+            BloodyMary => write!(f, "Bloody Mary"),
+            BaptismByFire => write!(f, "Baptism by Fire"),
+            IsaacsAwakening => write!(f, "Isaac's Awakening"),
+            SeeingDouble => write!(f, "Seeing Double"),
+            PicaRun => write!(f, "Pica Run"),
+            HotPotato => write!(f, "Hot Potato"),
+            Cantripped => write!(f, "Cantripped!"),
+            RedRedemption => write!(f, "Red Redemption"),
+            DeleteThis => write!(f, "DELETE THIS"),
+        }
+    }
+}
+
+impl FromStr for Challenge {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Challenge::*;
+        match s {
+            "Pitch Black" => Ok(PitchBlack),
+            "High Brow" => Ok(HighBrow),
+            "Head Trauma" => Ok(HeadTrauma),
+            "Darkness Falls" => Ok(DarknessFalls),
+            "The Tank" => Ok(TheTank),
+            "Solar System" => Ok(SolarSystem),
+            "Suicide King" => Ok(SuicideKing),
+            "Cat Got Your Tongue" => Ok(CatGotYourTongue),
+            "Demo Man" => Ok(DemoMan),
+            "Cursed!" => Ok(Cursed),
+            "Glass Cannon" => Ok(GlassCannon),
+            "When Life Gives You Lemons" => Ok(WhenLifeGivesYouLemons),
+            "Beans!" => Ok(Beans),
+            "It's In The Cards" => Ok(ItsInTheCards),
+            "Slow Roll" => Ok(SlowRoll),
+            "Computer Savvy" => Ok(ComputerSavvy),
+            "Waka Waka" => Ok(WakaWaka),
+            "The Host" => Ok(TheHost),
+            "The Family Man" => Ok(TheFamilyMan),
+            "Purist" => Ok(Purist),
+            "XXXXXXXXL" => Ok(Xxxxxxxxl),
+            "SPEED!" => Ok(Speed),
+            "Blue Bomber" => Ok(BlueBomber),
+            "PAY TO PLAY" => Ok(PayToPlay),
+            "Have a Heart" => Ok(HaveAHeart),
+            "I RULE!" => Ok(IRule),
+            "BRAINS!" => Ok(Brains),
+            "PRIDE DAY!" => Ok(PrideDay),
+            "Onan's Streak" => Ok(OnansStreak),
+            "The Guardian" => Ok(TheGuardian),
+            "Backasswards" => Ok(Backasswards),
+            "Aprils Fool" => Ok(AprilsFool),
+            "Pokey Mans" => Ok(PokeyMans),
+            "Ultra Hard" => Ok(UltraHard),
+            "Pong" => Ok(Pong),
+            "Scat Man" => Ok(ScatMan),
+            "Bloody Mary" => Ok(BloodyMary),
+            "Baptism by Fire" => Ok(BaptismByFire),
+            "Isaac's Awakening" => Ok(IsaacsAwakening),
+            "Seeing Double" => Ok(SeeingDouble),
+            "Pica Run" => Ok(PicaRun),
+            "Hot Potato" => Ok(HotPotato),
+            "Cantripped!" => Ok(Cantripped),
+            "Red Redemption" => Ok(RedRedemption),
+            "DELETE THIS" => Ok(DeleteThis),
+            _ => Err("Could not convert string to Challenge"),
+        }
+    }
+}
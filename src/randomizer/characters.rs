@@ -1,4 +1,5 @@
-use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency, Mantle};
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
 use crate::randomizer::targets::Target;
 use std::fmt;
 use std::str::FromStr;
@@ -46,13 +47,26 @@ pub enum Character {
 impl Character {
     pub fn is_tainted(&self) -> bool {
         use Character::*;
-        match self {
-            TaintedIsaac | TaintedMagdalene | TaintedCain | TaintedJudas | TaintedBlueBaby
-            | TaintedEve | TaintedSamson | TaintedAzazel | TaintedLazarus | TaintedEden
-            | TaintedLost | TaintedLilith | TaintedKeeper | TaintedApollyon | TaintedForgotten
-            | TaintedBethany | TaintedJacob => true,
-            _ => false,
-        }
+        matches!(
+            self,
+            TaintedIsaac
+                | TaintedMagdalene
+                | TaintedCain
+                | TaintedJudas
+                | TaintedBlueBaby
+                | TaintedEve
+                | TaintedSamson
+                | TaintedAzazel
+                | TaintedLazarus
+                | TaintedEden
+                | TaintedLost
+                | TaintedLilith
+                | TaintedKeeper
+                | TaintedApollyon
+                | TaintedForgotten
+                | TaintedBethany
+                | TaintedJacob
+        )
     }
 
     pub fn tainted_to_normal(&self) -> Option<Character> {
@@ -78,6 +92,47 @@ impl Character {
             _ => None,
         }
     }
+
+    // id of the game achievement unlocking the character, Isaac is there from the start
+    pub fn achievement_id(&self) -> Option<u16> {
+        use Character::*;
+        match self {
+            Isaac => None,
+            Magdalene => Some(1),
+            Cain => Some(2),
+            Judas => Some(3),
+            BlueBaby => Some(32),
+            Eve => Some(42),
+            Samson => Some(67),
+            Azazel => Some(79),
+            Lazarus => Some(80),
+            Eden => Some(81),
+            Lost => Some(82),
+            Lilith => Some(199),
+            Keeper => Some(251),
+            Apollyon => Some(340),
+            Forgotten => Some(390),
+            Bethany => Some(404),
+            JacobAndEsau => Some(405),
+            TaintedIsaac => Some(474),
+            TaintedMagdalene => Some(475),
+            TaintedCain => Some(476),
+            TaintedJudas => Some(477),
+            TaintedBlueBaby => Some(478),
+            TaintedEve => Some(479),
+            TaintedSamson => Some(480),
+            TaintedAzazel => Some(481),
+            TaintedLazarus => Some(482),
+            TaintedEden => Some(483),
+            TaintedLost => Some(484),
+            TaintedLilith => Some(485),
+            TaintedKeeper => Some(486),
+            TaintedApollyon => Some(487),
+            TaintedForgotten => Some(488),
+            TaintedBethany => Some(489),
+            TaintedJacob => Some(490),
+        }
+    }
 }
 
 impl fmt::Display for Character {
@@ -99,7 +154,7 @@ impl fmt::Display for Character {
             Keeper => write!(f, "Keeper"),
             Apollyon => write!(f, "Apollyon"),
             Forgotten => write!(f, "Forgotten"),
-            Bethany => write!(f, "Behtany"),
+            Bethany => write!(f, "Bethany"),
             JacobAndEsau => write!(f, "Jacob & Esau"),
             TaintedIsaac => write!(f, "Tainted Isaac"),
             TaintedMagdalene => write!(f, "Tainted Magdalene"),
@@ -108,7 +163,7 @@ impl fmt::Display for Character {
             TaintedBlueBaby => write!(f, "Tainted ???"),
             TaintedEve => write!(f, "Tainted Eve"),
             TaintedSamson => write!(f, "Tainted Samson"),
-            TaintedAzazel => write!(f, "Tainted Azazael"),
+            TaintedAzazel => write!(f, "Tainted Azazel"),
             TaintedLazarus => write!(f, "Tainted Lazarus"),
             TaintedEden => write!(f, "Tainted Eden"),
             TaintedLost => write!(f, "Tainted Lost"),
@@ -116,7 +171,7 @@ impl fmt::Display for Character {
             TaintedKeeper => write!(f, "Tainted Keeper"),
             TaintedApollyon => write!(f, "Tainted Apollyon"),
             TaintedForgotten => write!(f, "Tainted Forgotten"),
-            TaintedBethany => write!(f, "Tainted Behtany"),
+            TaintedBethany => write!(f, "Tainted Bethany"),
             TaintedJacob => write!(f, "Tainted Jacob"),
         }
     }
@@ -152,7 +207,7 @@ impl FromStr for Character {
             "Tainted ???" => Ok(TaintedBlueBaby),
             "Tainted Eve" => Ok(TaintedEve),
             "Tainted Samson" => Ok(TaintedSamson),
-            "Tainted Azazael" => Ok(TaintedAzazel),
+            "Tainted Azazel" => Ok(TaintedAzazel),
             "Tainted Lazarus" => Ok(TaintedLazarus),
             "Tainted Eden" => Ok(TaintedEden),
             "Tainted Lost" => Ok(TaintedLost),
@@ -160,7 +215,7 @@ impl FromStr for Character {
             "Tainted Keeper" => Ok(TaintedKeeper),
             "Tainted Apollyon" => Ok(TaintedApollyon),
             "Tainted Forgotten" => Ok(TaintedForgotten),
-            "Tainted Behtany" => Ok(TaintedBethany),
+            "Tainted Bethany" => Ok(TaintedBethany),
             "Tainted Jacob" => Ok(TaintedJacob),
             _ => Err("Could not convert string to Character"),
         }
@@ -173,10 +228,16 @@ impl HasDependency for Character {
         use Dependency::*;
 
         if self.is_tainted() {
-            return Product(vec![
-                DependencyValue::Target(Target::Beast),
+            let mut deps = vec![
+                DependencyValue::Target(Target::Home),
                 DependencyValue::Character(self.tainted_to_normal().unwrap()),
-            ]);
+            ];
+            match self {
+                TaintedLost => deps.push(DependencyValue::Achievement(Achievement::HolyMantle)),
+                TaintedKeeper => deps.push(DependencyValue::Target(Target::Greedier)),
+                _ => {}
+            }
+            return Product(deps);
         }
 
         match self {
@@ -190,7 +251,7 @@ impl HasDependency for Character {
             Azazel => None,
             Lazarus => None,
             Eden => Singular(DependencyValue::Target(Target::Heart)),
-            Lost => Singular(DependencyValue::Mantle(Mantle)),
+            Lost => Singular(DependencyValue::Achievement(Achievement::HolyMantle)),
             Lilith => Product(vec![
                 DependencyValue::Character(Azazel),
                 DependencyValue::Target(Target::UltraGreed),
@@ -198,7 +259,10 @@ impl HasDependency for Character {
             Keeper => Singular(DependencyValue::Target(Target::UltraGreed)),
             Apollyon => Singular(DependencyValue::Target(Target::MegaSatan)),
             Forgotten => None,
-            Bethany => Singular(DependencyValue::Character(Lazarus)),
+            Bethany => Product(vec![
+                DependencyValue::Character(Lazarus),
+                DependencyValue::Target(Target::Heart),
+            ]),
             JacobAndEsau => Singular(DependencyValue::Target(Target::Mother)),
             _ => None,
         }
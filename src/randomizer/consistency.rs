@@ -0,0 +1,387 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::dependency::{Dependency, DependencyValue};
+use crate::randomizer::pool::Unlocks;
+use crate::randomizer::targets::Target;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
+
+// an unlock state the game can't get into
+#[derive(Clone, Debug)]
+pub enum Contradiction {
+    // `missing` is the part of the dependency of `value` that isn't unlocked
+    UnmetDependency {
+        value: DependencyValue,
+        missing: Dependency,
+    },
+    MarksOnLockedCharacter(Character),
+    MarkOnLockedTarget {
+        character: Character,
+        target: Target,
+    },
+}
+
+impl fmt::Display for Contradiction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Contradiction::*;
+        match self {
+            UnmetDependency { value, missing } => {
+                write!(f, "{} is unlocked, but it depends on {}", value, missing)
+            }
+            MarksOnLockedCharacter(ch) => write!(f, "{} has marks, but is not unlocked", ch),
+            MarkOnLockedTarget { character, target } => write!(
+                f,
+                "{} has a {} mark, but {} is not unlocked",
+                character, target, target
+            ),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum RepairMode {
+    // unlock everything the marks and unlocks depend on
+    Infer,
+    // lock everything whose dependency isn't met and remove the marks that need it
+    Strip,
+}
+
+impl fmt::Display for RepairMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RepairMode::Infer => write!(f, "infer"),
+            RepairMode::Strip => write!(f, "strip"),
+        }
+    }
+}
+
+impl FromStr for RepairMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "infer" => Ok(RepairMode::Infer),
+            "strip" => Ok(RepairMode::Strip),
+            _ => Err("Could not convert string to RepairMode"),
+        }
+    }
+}
+
+// a change made by a repair
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Fix {
+    Unlocked(DependencyValue),
+    Locked(DependencyValue),
+    RemovedMarks(Character),
+    RemovedMark {
+        character: Character,
+        target: Target,
+    },
+}
+
+impl fmt::Display for Fix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Fix::*;
+        match self {
+            Unlocked(val) => write!(f, "unlocked {}", val),
+            Locked(val) => write!(f, "locked {}", val),
+            RemovedMarks(ch) => write!(f, "removed every mark of {}", ch),
+            RemovedMark { character, target } => {
+                write!(f, "removed the {} mark of {}", target, character)
+            }
+        }
+    }
+}
+
+impl Unlocks {
+    // every contradiction, in the order of the dependency graph nodes and then of the marks
+    pub fn validate(&self) -> Vec<Contradiction> {
+        let mut contradictions = Vec::new();
+
+        for val in DependencyValue::all() {
+            if !self.is_dependency_val_unlocked(&val) {
+                continue;
+            }
+            if let Some(missing) = self.unmet_part(&self.dependency_of(&val)) {
+                contradictions.push(Contradiction::UnmetDependency {
+                    value: val,
+                    missing,
+                });
+            }
+        }
+
+        for ch in Character::iter() {
+            let Some(marks) = self.marks().get(&ch) else {
+                continue;
+            };
+            if !self.unlocked_chars().contains(&ch) {
+                contradictions.push(Contradiction::MarksOnLockedCharacter(ch));
+            }
+
+            let marked: HashSet<Target> = marks.iter().map(|(targ, _)| *targ).collect();
+            for targ in Target::iter() {
+                if marked.contains(&targ) && !self.unlocked_targets().contains(&targ) {
+                    contradictions.push(Contradiction::MarkOnLockedTarget {
+                        character: ch,
+                        target: targ,
+                    });
+                }
+            }
+        }
+
+        contradictions
+    }
+
+    // fixes contradictions until there are none left, fixing one can uncover others further down
+    // the dependency graph
+    pub fn repair(&mut self, mode: RepairMode) -> Vec<Fix> {
+        let mut fixes = Vec::new();
+
+        loop {
+            let fixes_before = fixes.len();
+            for contradiction in self.validate() {
+                self.fix(&contradiction, mode, &mut fixes);
+            }
+            if fixes.len() == fixes_before {
+                break;
+            }
+        }
+
+        fixes
+    }
+
+    fn fix(&mut self, contradiction: &Contradiction, mode: RepairMode, fixes: &mut Vec<Fix>) {
+        use Contradiction::*;
+
+        match (contradiction, mode) {
+            (UnmetDependency { missing, .. }, RepairMode::Infer) => {
+                let vals = match missing {
+                    Dependency::None => vec![],
+                    Dependency::Singular(val) => vec![val.clone()],
+                    Dependency::Product(vals) => vals.clone(),
+                    // any of them is enough, the first one is as good a guess as any other
+                    Dependency::Sum(vals) => vals.iter().take(1).cloned().collect(),
+                };
+                for val in vals {
+                    self.set_dependency_val_unlocked(&val, true, fixes);
+                }
+            }
+            (UnmetDependency { value, .. }, RepairMode::Strip) => {
+                self.set_dependency_val_unlocked(value, false, fixes);
+            }
+            (MarksOnLockedCharacter(ch), RepairMode::Infer) => {
+                let val = DependencyValue::Character(*ch);
+                self.set_dependency_val_unlocked(&val, true, fixes);
+            }
+            (MarksOnLockedCharacter(ch), RepairMode::Strip) => {
+                if self.marks().contains_key(ch) {
+                    self.remove_all_marks(ch);
+                    fixes.push(Fix::RemovedMarks(*ch));
+                }
+            }
+            (MarkOnLockedTarget { target, .. }, RepairMode::Infer) => {
+                let val = DependencyValue::Target(*target);
+                self.set_dependency_val_unlocked(&val, true, fixes);
+            }
+            (MarkOnLockedTarget { character, target }, RepairMode::Strip) => {
+                if self.marks().get(character).is_some_and(|marks| {
+                    marks.iter().any(|(marked, _)| marked == target)
+                }) {
+                    self.remove_marks(character, &HashSet::from([*target]));
+                    fixes.push(Fix::RemovedMark {
+                        character: *character,
+                        target: *target,
+                    });
+                }
+            }
+        }
+    }
+
+    // locking a character or target also removes its marks
+    fn set_dependency_val_unlocked(
+        &mut self,
+        val: &DependencyValue,
+        is_unlocked: bool,
+        fixes: &mut Vec<Fix>,
+    ) {
+        if self.is_dependency_val_unlocked(val) == is_unlocked {
+            return;
+        }
+
+        // Mom's Heart is unlocked with the Heart target
+        let targ = match val {
+            DependencyValue::Character(ch) => {
+                if is_unlocked {
+                    self.add_unlocked_chars(HashSet::from([*ch]));
+                } else {
+                    self.remove_unlocked_chars(&HashSet::from([*ch]));
+                }
+                None
+            }
+            DependencyValue::Achievement(achievement) => {
+                self.set_achievement_unlocked(*achievement, is_unlocked);
+                None
+            }
+            DependencyValue::Target(targ) => Some(*targ),
+            DependencyValue::Mom(_) => Some(Target::Heart),
+        };
+        if let Some(targ) = targ {
+            if is_unlocked {
+                self.add_unlocked_targets(HashSet::from([targ]));
+            } else {
+                self.remove_unlocked_targets(&HashSet::from([targ]));
+            }
+        }
+
+        fixes.push(if is_unlocked {
+            Fix::Unlocked(val.clone())
+        } else {
+            Fix::Locked(val.clone())
+        });
+    }
+
+    // the values of `dep` that keep it from being met, None if it is met
+    fn unmet_part(&self, dep: &Dependency) -> Option<Dependency> {
+        if self.is_unlocked_now(dep) {
+            return None;
+        }
+
+        match dep {
+            Dependency::Product(vals) => {
+                let mut missing: Vec<DependencyValue> = vals
+                    .iter()
+                    .filter(|val| !self.is_dependency_val_unlocked(val))
+                    .cloned()
+                    .collect();
+                if missing.len() == 1 {
+                    Some(Dependency::Singular(missing.remove(0)))
+                } else {
+                    Some(Dependency::Product(missing))
+                }
+            }
+            _ => Some(dep.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::randomizer::characters::Character;
+    use crate::randomizer::difficulty::Difficulty;
+
+    fn missing_of(contradictions: &[Contradiction], val: &DependencyValue) -> Option<Dependency> {
+        contradictions.iter().find_map(|contradiction| match contradiction {
+            Contradiction::UnmetDependency { value, missing } if value == val => {
+                Some(missing.clone())
+            }
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn tainted_without_home_is_a_contradiction() {
+        let mut unlocks = Unlocks::default();
+        unlocks.add_unlocked_chars(HashSet::from([Character::TaintedIsaac]));
+
+        let missing = missing_of(
+            &unlocks.validate(),
+            &DependencyValue::Character(Character::TaintedIsaac),
+        );
+        assert!(matches!(
+            missing,
+            Some(Dependency::Singular(DependencyValue::Target(Target::Home)))
+        ));
+    }
+
+    #[test]
+    fn home_mark_opens_the_closet() {
+        let mut unlocks = Unlocks::default();
+        unlocks.set_marks(Character::Isaac, HashSet::from([(Target::Home, Difficulty::Normal)]));
+        unlocks.add_unlocked_chars(HashSet::from([Character::TaintedIsaac]));
+
+        let val = DependencyValue::Character(Character::TaintedIsaac);
+        assert!(missing_of(&unlocks.validate(), &val).is_none());
+    }
+
+    #[test]
+    fn default_unlocks_are_consistent() {
+        assert!(Unlocks::default().validate().is_empty());
+    }
+
+    #[test]
+    fn finds_marks_on_locked_characters_and_targets() {
+        let mut unlocks = Unlocks::default();
+        unlocks
+            .set_saved_marks(Character::Cain, HashSet::from([(Target::Mom, Difficulty::Normal)]))
+            .set_saved_marks(Character::Isaac, HashSet::from([(Target::Hush, Difficulty::Normal)]));
+
+        let contradictions = unlocks.validate();
+        assert!(contradictions
+            .iter()
+            .any(|c| matches!(c, Contradiction::MarksOnLockedCharacter(Character::Cain))));
+        assert!(contradictions.iter().any(|c| matches!(
+            c,
+            Contradiction::MarkOnLockedTarget {
+                character: Character::Isaac,
+                target: Target::Hush
+            }
+        )));
+    }
+
+    #[test]
+    fn strip_removes_impossible_marks() {
+        let mut unlocks = Unlocks::default();
+        unlocks
+            .set_saved_marks(Character::Cain, HashSet::from([(Target::Mom, Difficulty::Normal)]))
+            .set_saved_marks(Character::Isaac, HashSet::from([(Target::Hush, Difficulty::Normal)]));
+
+        let fixes = unlocks.repair(RepairMode::Strip);
+        assert!(fixes.contains(&Fix::RemovedMarks(Character::Cain)));
+        assert!(fixes.contains(&Fix::RemovedMark {
+            character: Character::Isaac,
+            target: Target::Hush
+        }));
+        assert!(!unlocks.marks().contains_key(&Character::Cain));
+        assert!(!unlocks.has_mark(&Character::Isaac, Target::Hush, Difficulty::Normal));
+        assert!(!unlocks.unlocked_targets().contains(&Target::Hush));
+        assert!(unlocks.validate().is_empty());
+    }
+
+    #[test]
+    fn infer_unlocks_what_marks_need() {
+        let mut unlocks = Unlocks::default();
+        unlocks
+            .set_saved_marks(Character::Cain, HashSet::from([(Target::Mom, Difficulty::Normal)]))
+            .set_saved_marks(Character::Isaac, HashSet::from([(Target::Hush, Difficulty::Normal)]));
+
+        let fixes = unlocks.repair(RepairMode::Infer);
+        assert!(fixes.contains(&Fix::Unlocked(DependencyValue::Character(Character::Cain))));
+        assert!(fixes.contains(&Fix::Unlocked(DependencyValue::Target(Target::Hush))));
+        assert!(unlocks.has_mark(&Character::Isaac, Target::Hush, Difficulty::Normal));
+        assert!(unlocks.validate().is_empty());
+    }
+
+    #[test]
+    fn strip_locks_tainted_without_home() {
+        let mut unlocks = Unlocks::default();
+        unlocks.add_unlocked_chars(HashSet::from([Character::TaintedIsaac]));
+
+        let fixes = unlocks.repair(RepairMode::Strip);
+        let tainted = DependencyValue::Character(Character::TaintedIsaac);
+        assert_eq!(fixes, vec![Fix::Locked(tainted)]);
+        assert!(!unlocks.unlocked_chars().contains(&Character::TaintedIsaac));
+    }
+
+    #[test]
+    fn infer_unlocks_home_for_tainted() {
+        let mut unlocks = Unlocks::default();
+        unlocks.add_unlocked_chars(HashSet::from([Character::TaintedIsaac]));
+
+        let fixes = unlocks.repair(RepairMode::Infer);
+        assert!(fixes.contains(&Fix::Unlocked(DependencyValue::Target(Target::Home))));
+        assert!(unlocks.unlocked_chars().contains(&Character::TaintedIsaac));
+        assert!(unlocks.validate().is_empty());
+    }
+}
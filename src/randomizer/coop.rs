@@ -0,0 +1,53 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::targets::Target;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+
+// whether a co-op target has to be useful for one of the characters or for all of them
+#[derive(Hash, Eq, PartialEq, Debug, Clone, Copy, Default)]
+pub enum CoopTargets {
+    #[default]
+    Any,
+    All,
+}
+
+impl fmt::Display for CoopTargets {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use CoopTargets::*;
+        match self {
+            Any => write!(f, "any"),
+            All => write!(f, "all"),
+        }
+    }
+}
+
+impl FromStr for CoopTargets {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use CoopTargets::*;
+        match s {
+            "any" => Ok(Any),
+            "all" => Ok(All),
+            _ => Err("Could not convert string to CoopTargets"),
+        }
+    }
+}
+
+// one character per player, all going for the same targets
+#[derive(Clone, Debug)]
+pub struct CoopPick {
+    pub(crate) characters: Vec<Character>,
+    pub(crate) targets: HashSet<Target>,
+}
+
+impl CoopPick {
+    pub fn characters(&self) -> &Vec<Character> {
+        &self.characters
+    }
+
+    pub fn targets(&self) -> &HashSet<Target> {
+        &self.targets
+    }
+}
@@ -0,0 +1,103 @@
+use crate::randomizer::challenges::Challenge;
+use crate::randomizer::characters::Character;
+use crate::randomizer::donations::Donation;
+use crate::randomizer::planner;
+use crate::randomizer::pool::Unlocks;
+use crate::randomizer::targets::Target;
+use rand::Rng;
+use std::collections::HashSet;
+use strum::IntoEnumIterator;
+
+// one thing to do on the way to 100% completion
+#[derive(Clone, Debug)]
+pub enum Task {
+    Run {
+        character: Character,
+        targets: HashSet<Target>,
+        // marks the run gives, counted like `done` adds them
+        marks: usize,
+        unlocks: Vec<String>,
+    },
+    Challenge(Challenge),
+    Donation(Donation),
+}
+
+impl Task {
+    // roughly how many achievements doing the task gets
+    pub fn worth(&self) -> usize {
+        match self {
+            Task::Run { marks, unlocks, .. } => marks + unlocks.len(),
+            Task::Challenge(_) | Task::Donation(_) => 1,
+        }
+    }
+}
+
+// rolls are random, so a few of them are sampled and the best one of each character is kept
+const SAMPLED_RUNS: usize = 16;
+
+// the best sampled run of each character, a rolled challenge and the donation machines that
+// aren't full yet, in the order they are preferred when worth the same
+pub fn candidate_tasks<R: Rng + ?Sized>(unlocks: &Unlocks, rng: &mut R) -> Vec<Task> {
+    let mut tasks: Vec<Task> = Vec::new();
+
+    for _ in 0..SAMPLED_RUNS {
+        let (character, targets) = match unlocks.get_random_pick_with_rng(rng) {
+            Some(pick) => pick,
+            None => break,
+        };
+        let run = run_task(unlocks, character, targets);
+
+        let same_character = tasks.iter().position(
+            |task| matches!(task, Task::Run { character: ch, .. } if *ch == character),
+        );
+        match same_character {
+            Some(pos) if tasks[pos].worth() < run.worth() => tasks[pos] = run,
+            Some(_) => {}
+            None => tasks.push(run),
+        }
+    }
+
+    if let Some(challenge) = unlocks.get_random_challenge(rng) {
+        tasks.push(Task::Challenge(challenge));
+    }
+
+    tasks.extend(
+        Donation::iter()
+            .filter(|donation| !unlocks.completed_donations().contains(donation))
+            .map(Task::Donation),
+    );
+
+    tasks.retain(|task| task.worth() > 0);
+    tasks
+}
+
+// the marks are counted like marking the run done would add them
+fn run_task(unlocks: &Unlocks, character: Character, targets: HashSet<Target>) -> Task {
+    let difficulty = unlocks.required_difficulty();
+    let marked = if unlocks.imply_marks() {
+        Target::with_passed_through(&targets)
+    } else {
+        targets.clone()
+    };
+    let marks = marked
+        .iter()
+        .filter(|targ| unlocks.is_target_significant(targ))
+        .filter(|targ| !unlocks.has_mark(&character, **targ, difficulty))
+        .count();
+
+    Task::Run {
+        character,
+        unlocks: planner::unlocks_of_run(unlocks, character, &targets),
+        targets,
+        marks,
+    }
+}
+
+// the candidate worth the most, None once there's nothing left to do
+pub fn next_task<R: Rng + ?Sized>(unlocks: &Unlocks, rng: &mut R) -> Option<Task> {
+    // max_by_key keeps the last of equal elements, so the preferred ones have to come last
+    candidate_tasks(unlocks, rng)
+        .into_iter()
+        .rev()
+        .max_by_key(|task| task.worth())
+}
@@ -1,6 +1,11 @@
+use crate::randomizer::achievements::Achievement;
 use crate::randomizer::characters::Character;
 use crate::randomizer::targets::Target;
+use std::fmt;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 
+#[derive(Clone, Debug)]
 pub enum Dependency {
     None,
     Singular(DependencyValue),
@@ -8,15 +13,45 @@ pub enum Dependency {
     Product(Vec<DependencyValue>),
 }
 
-#[derive(Clone)]
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Dependency::*;
+
+        let join = |vals: &Vec<DependencyValue>| -> String {
+            vals.iter()
+                .map(|val| val.to_string())
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        match self {
+            None => write!(f, "nothing"),
+            Singular(val) => write!(f, "{}", val),
+            Sum(vals) => write!(f, "any of [{}]", join(vals)),
+            Product(vals) => write!(f, "all of [{}]", join(vals)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum DependencyValue {
     Character(Character),
     Target(Target),
-    Mantle(Mantle),
-    ItLives(ItLives),
+    Achievement(Achievement),
     Mom(Mom),
-    Polaroid(Polaroid),
-    Negative(Negative),
+}
+
+impl DependencyValue {
+    // every node of the dependency graph
+    pub fn all() -> Vec<DependencyValue> {
+        let mut vals: Vec<DependencyValue> =
+            Character::iter().map(DependencyValue::Character).collect();
+        vals.extend(Target::iter().map(DependencyValue::Target));
+        vals.extend(Achievement::TRACKED.into_iter().map(DependencyValue::Achievement));
+        vals.push(DependencyValue::Mom(Mom));
+
+        vals
+    }
 }
 
 impl HasDependency for DependencyValue {
@@ -26,34 +61,47 @@ impl HasDependency for DependencyValue {
         match self {
             Character(ch) => ch.depends_on(),
             Target(targ) => targ.depends_on(),
-            Mantle(mantle) => mantle.depends_on(),
-            ItLives(itlives) => itlives.depends_on(),
+            Achievement(achievement) => achievement.depends_on(),
             Mom(mom) => mom.depends_on(),
-            Polaroid(polaroid) => polaroid.depends_on(),
-            Negative(negative) => negative.depends_on(),
         }
     }
 }
 
-#[derive(Clone)]
-pub struct Mantle;
+impl fmt::Display for DependencyValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use DependencyValue::*;
 
-impl HasDependency for Mantle {
-    fn depends_on(&self) -> Dependency {
-        Dependency::Singular(DependencyValue::Target(Target::UltraGreed))
+        match self {
+            Character(ch) => write!(f, "character {}", ch),
+            Target(targ) => write!(f, "target {}", targ),
+            Achievement(achievement) => write!(f, "{}", achievement),
+            Mom(_) => write!(f, "Mom's Heart"),
+        }
     }
 }
 
-#[derive(Clone)]
-pub struct ItLives;
+impl FromStr for DependencyValue {
+    type Err = &'static str;
 
-impl HasDependency for ItLives {
-    fn depends_on(&self) -> Dependency {
-        Dependency::Singular(DependencyValue::Target(Target::Heart))
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(ch) = s.strip_prefix("character ") {
+            return Ok(DependencyValue::Character(Character::from_str(ch)?));
+        }
+        if let Some(targ) = s.strip_prefix("target ") {
+            return Ok(DependencyValue::Target(Target::from_str(targ)?));
+        }
+
+        match s {
+            "Mom's Heart" => Ok(DependencyValue::Mom(Mom)),
+            _ => match Achievement::from_str(s) {
+                Ok(achievement) => Ok(DependencyValue::Achievement(achievement)),
+                Err(_) => Err("Could not convert string to DependencyValue"),
+            },
+        }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Mom;
 
 impl HasDependency for Mom {
@@ -62,24 +110,6 @@ impl HasDependency for Mom {
     }
 }
 
-#[derive(Clone)]
-pub struct Polaroid;
-
-impl HasDependency for Polaroid {
-    fn depends_on(&self) -> Dependency {
-        Dependency::Singular(DependencyValue::Target(Target::Isaac))
-    }
-}
-
-#[derive(Clone)]
-pub struct Negative;
-
-impl HasDependency for Negative {
-    fn depends_on(&self) -> Dependency {
-        Dependency::Singular(DependencyValue::Target(Target::Satan))
-    }
-}
-
 pub trait HasDependency {
     fn depends_on(&self) -> Dependency;
 }
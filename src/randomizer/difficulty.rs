@@ -0,0 +1,34 @@
+use std::fmt;
+use std::str::FromStr;
+use strum_macros::EnumIter;
+
+// difficulty a completion mark was earned on, a hard mark also counts as the normal one
+#[derive(EnumIter, Hash, Eq, PartialEq, Debug, Clone, Copy)]
+#[repr(C)]
+pub enum Difficulty {
+    Normal,
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Difficulty::*;
+        match self {
+            Normal => write!(f, "Normal"),
+            Hard => write!(f, "Hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Difficulty::*;
+        match s {
+            "Normal" => Ok(Normal),
+            "Hard" => Ok(Hard),
+            _ => Err("Could not convert string to Difficulty"),
+        }
+    }
+}
@@ -0,0 +1,33 @@
+use std::fmt;
+use std::str::FromStr;
+use strum_macros::{EnumCount as EnumCountMacro, EnumIter};
+
+// machines that give achievements while they are filled up with coins
+#[derive(EnumIter, EnumCountMacro, Hash, Eq, PartialEq, Debug, Clone, Copy)]
+pub enum Donation {
+    DonationMachine,
+    GreedDonationMachine,
+}
+
+impl fmt::Display for Donation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Donation::*;
+        match self {
+            DonationMachine => write!(f, "Donation Machine"),
+            GreedDonationMachine => write!(f, "Greed Donation Machine"),
+        }
+    }
+}
+
+impl FromStr for Donation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Donation::*;
+        match s {
+            "Donation Machine" => Ok(DonationMachine),
+            "Greed Donation Machine" => Ok(GreedDonationMachine),
+            _ => Err("Could not convert string to Donation"),
+        }
+    }
+}
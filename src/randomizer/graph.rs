@@ -0,0 +1,162 @@
+use crate::randomizer::dependency::{Dependency, DependencyValue};
+use crate::randomizer::pool::Unlocks;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum GraphFormat {
+    #[default]
+    Dot,
+    Mermaid,
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GraphFormat::Dot => write!(f, "dot"),
+            GraphFormat::Mermaid => write!(f, "mermaid"),
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            _ => Err("Could not convert string to GraphFormat"),
+        }
+    }
+}
+
+struct Node {
+    id: String,
+    label: String,
+    is_unlocked: bool,
+    // any of/all of nodes joining several dependencies
+    is_joint: bool,
+}
+
+// edges go from a dependency to what it unlocks
+struct Graph {
+    nodes: Vec<Node>,
+    edges: Vec<(String, String)>,
+}
+
+impl Graph {
+    fn new(unlocks: &Unlocks) -> Self {
+        let mut graph = Graph {
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for val in DependencyValue::all() {
+            let id = node_id(&val);
+            graph.nodes.push(Node {
+                id: id.clone(),
+                // characters and targets share names (Isaac, ???), the kind stays in the label
+                label: val.to_string(),
+                is_unlocked: unlocks.is_dependency_val_unlocked(&val),
+                is_joint: false,
+            });
+
+            let dep = unlocks.ruleset().depends_on(&val);
+            match &dep {
+                Dependency::None => {}
+                Dependency::Singular(dep_val) => graph.edges.push((node_id(dep_val), id)),
+                Dependency::Sum(_) | Dependency::Product(_) => graph.add_joint(unlocks, &dep, id),
+            }
+        }
+
+        graph
+    }
+
+    fn add_joint(&mut self, unlocks: &Unlocks, dep: &Dependency, to: String) {
+        let (joint_id, label, dep_vals) = match dep {
+            Dependency::Sum(vals) => (format!("any_{}", to), "any of", vals),
+            Dependency::Product(vals) => (format!("all_{}", to), "all of", vals),
+            _ => return,
+        };
+
+        self.nodes.push(Node {
+            id: joint_id.clone(),
+            label: label.to_string(),
+            is_unlocked: unlocks.is_unlocked_now(dep),
+            is_joint: true,
+        });
+        for dep_val in dep_vals {
+            self.edges.push((node_id(dep_val), joint_id.clone()));
+        }
+        self.edges.push((joint_id, to));
+    }
+
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph unlocks {\n    rankdir=LR;\n");
+
+        for node in &self.nodes {
+            let shape = if node.is_joint { "diamond" } else { "box" };
+            let fill = if node.is_unlocked { "palegreen" } else { "white" };
+            out.push_str(&format!(
+                "    {} [label=\"{}\", shape={}, style=filled, fillcolor={}];\n",
+                node.id, node.label, shape, fill
+            ));
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("    {} -> {};\n", from, to));
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    fn to_mermaid(&self) -> String {
+        let mut out = String::from("graph LR\n");
+
+        for node in &self.nodes {
+            if node.is_joint {
+                out.push_str(&format!("    {}{{\"{}\"}}\n", node.id, node.label));
+            } else {
+                out.push_str(&format!("    {}[\"{}\"]\n", node.id, node.label));
+            }
+        }
+        for (from, to) in &self.edges {
+            out.push_str(&format!("    {} --> {}\n", from, to));
+        }
+
+        let unlocked: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| node.is_unlocked)
+            .map(|node| node.id.as_str())
+            .collect();
+        if !unlocked.is_empty() {
+            out.push_str("    classDef unlocked fill:#98fb98\n");
+            out.push_str(&format!("    class {} unlocked\n", unlocked.join(",")));
+        }
+
+        out
+    }
+}
+
+// the whole dependency graph, unlocked nodes are colored
+pub fn render(unlocks: &Unlocks, format: GraphFormat) -> String {
+    let graph = Graph::new(unlocks);
+
+    match format {
+        GraphFormat::Dot => graph.to_dot(),
+        GraphFormat::Mermaid => graph.to_mermaid(),
+    }
+}
+
+fn node_id(val: &DependencyValue) -> String {
+    use DependencyValue as Val;
+
+    match val {
+        Val::Character(ch) => format!("char_{}", *ch as u32),
+        Val::Target(targ) => format!("targ_{}", *targ as u32),
+        Val::Achievement(achievement) => format!("ach_{}", achievement.id()),
+        Val::Mom(_) => "mom".to_string(),
+    }
+}
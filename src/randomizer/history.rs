@@ -0,0 +1,132 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::targets::Target;
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Rolled,
+    Done,
+    Failed,
+    Vetoed,
+}
+
+// padded so outcomes line up in the history
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Outcome::*;
+        f.pad(match self {
+            Rolled => "rolled",
+            Done => "done",
+            Failed => "failed",
+            Vetoed => "vetoed",
+        })
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Outcome::*;
+        match s {
+            "rolled" => Ok(Rolled),
+            "done" => Ok(Done),
+            "failed" => Ok(Failed),
+            "vetoed" => Ok(Vetoed),
+            _ => Err("Could not convert string to Outcome"),
+        }
+    }
+}
+
+// what the reroll limit is counted over
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum RerollPeriod {
+    // since the last roll that was done or failed
+    #[default]
+    Session,
+    // since midnight UTC
+    Day,
+}
+
+impl fmt::Display for RerollPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RerollPeriod::Session => write!(f, "session"),
+            RerollPeriod::Day => write!(f, "day"),
+        }
+    }
+}
+
+impl FromStr for RerollPeriod {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "session" => Ok(RerollPeriod::Session),
+            "day" => Ok(RerollPeriod::Day),
+            _ => Err("Could not convert string to RerollPeriod"),
+        }
+    }
+}
+
+// the oldest rolls are dropped past this many, the no-repeat rules don't look further back
+pub const MAX_HISTORY: usize = 500;
+
+pub fn now_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Clone, Debug)]
+pub struct RollRecord {
+    timestamp: u64,
+    character: Character,
+    targets: HashSet<Target>,
+    outcome: Outcome,
+}
+
+impl RollRecord {
+    pub fn new(
+        timestamp: u64,
+        character: Character,
+        targets: HashSet<Target>,
+        outcome: Outcome,
+    ) -> Self {
+        Self {
+            timestamp,
+            character,
+            targets,
+            outcome,
+        }
+    }
+
+    pub fn now(character: Character, targets: HashSet<Target>, outcome: Outcome) -> Self {
+        Self::new(now_timestamp(), character, targets, outcome)
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    pub fn targets(&self) -> &HashSet<Target> {
+        &self.targets
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        self.outcome
+    }
+
+    pub fn set_outcome(&mut self, outcome: Outcome) -> &mut Self {
+        self.outcome = outcome;
+        self
+    }
+}
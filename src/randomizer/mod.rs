@@ -1,4 +1,18 @@
+pub mod achievements;
+pub mod challenges;
 pub mod characters;
-mod dependency;
+pub mod consistency;
+pub mod coop;
+pub mod deadgod;
+pub mod dependency;
+pub mod difficulty;
+pub mod donations;
+pub mod graph;
+pub mod history;
+pub mod planner;
 pub mod pool;
+pub mod route;
+pub mod ruleset;
 pub mod targets;
+pub mod trace;
+pub mod weighting;
@@ -0,0 +1,331 @@
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::characters::Character;
+use crate::randomizer::dependency::{Dependency, DependencyValue, Mom};
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::pool::Unlocks;
+use crate::randomizer::targets::Target;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use strum::IntoEnumIterator;
+
+// no goal needs anywhere near this many runs, hitting it means the plan went in circles
+const MAX_RUNS: usize = 64;
+
+#[derive(Debug)]
+pub enum Error {
+    // nothing in the dependency graph leads to the goal, e.g. starting characters
+    Unreachable(String),
+    TooManyRuns,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Unreachable(what) => write!(f, "{} can't be unlocked by winning runs", what),
+            Error::TooManyRuns => write!(f, "gave up after {} runs", MAX_RUNS),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Run {
+    character: Character,
+    target: Target,
+    unlocks: Vec<String>,
+}
+
+impl Run {
+    pub fn character(&self) -> Character {
+        self.character
+    }
+
+    pub fn target(&self) -> Target {
+        self.target
+    }
+
+    // everything the run unlocks, in the order it gets unlocked
+    pub fn unlocks(&self) -> &Vec<String> {
+        &self.unlocks
+    }
+}
+
+// ordered list of runs that unlocks `goal` starting from `unlocks`
+pub fn plan(unlocks: &Unlocks, goal: &DependencyValue) -> Result<Vec<Run>, Error> {
+    let mut sim = unlocks.clone();
+    let mut runs = Vec::new();
+
+    loop {
+        // how many runs each alternative takes from the current state, only valid until the next run
+        let mut memo = HashMap::new();
+        let (ch, targ) = match next_run(&sim, goal, &mut memo)? {
+            Some(run) => run,
+            None => break,
+        };
+
+        if runs.len() == MAX_RUNS {
+            return Err(Error::TooManyRuns);
+        }
+
+        let unlocked = apply_run(&mut sim, ch, targ);
+        if unlocked.is_empty() {
+            // the run changed nothing, so the next one would be the same
+            return Err(Error::Unreachable(goal.to_string()));
+        }
+
+        runs.push(Run {
+            character: ch,
+            target: targ,
+            unlocks: unlocked,
+        });
+    }
+
+    Ok(runs)
+}
+
+// is_none_or would need Rust 1.82
+#[allow(clippy::unnecessary_map_or)]
+fn next_run(
+    sim: &Unlocks,
+    goal: &DependencyValue,
+    memo: &mut HashMap<DependencyValue, Option<usize>>,
+) -> Result<Option<(Character, Target)>, Error> {
+    use Dependency::*;
+
+    if sim.is_dependency_val_unlocked(goal) {
+        return Ok(Option::None);
+    }
+
+    let dep = sim.ruleset().depends_on(goal);
+    if sim.is_unlocked_now(&dep) {
+        return match run_for(sim, goal) {
+            Some(run) => Ok(Some(run)),
+            Option::None => Err(Error::Unreachable(goal.to_string())),
+        };
+    }
+
+    match dep {
+        None => Err(Error::Unreachable(goal.to_string())),
+        Singular(val) => next_run(sim, &val, memo),
+        Product(vals) => match vals.iter().find(|val| !sim.is_dependency_val_unlocked(val)) {
+            Some(val) => next_run(sim, val, memo),
+            Option::None => Err(Error::Unreachable(goal.to_string())),
+        },
+        Sum(vals) => {
+            // any of the values will do, take the one that is the fewest runs away
+            let mut shortest: Option<(usize, &DependencyValue)> = Option::None;
+            for val in &vals {
+                let runs = match memo.get(val) {
+                    Some(runs) => *runs,
+                    Option::None => {
+                        let runs = plan(sim, val).ok().map(|runs| runs.len());
+                        memo.insert(val.clone(), runs);
+                        runs
+                    }
+                };
+                if let Some(runs) = runs {
+                    if shortest.map_or(true, |(len, _)| runs < len) {
+                        shortest = Some((runs, val));
+                    }
+                }
+            }
+
+            match shortest {
+                Some((_, val)) => next_run(sim, val, memo),
+                Option::None => Err(Error::Unreachable(goal.to_string())),
+            }
+        }
+    }
+}
+
+// the run that unlocks `val` once everything it depends on is unlocked
+fn run_for(sim: &Unlocks, val: &DependencyValue) -> Option<(Character, Target)> {
+    use DependencyValue as Val;
+
+    let dep = sim.ruleset().depends_on(val);
+    let target = match val {
+        Val::Character(_) => {
+            return match runner_of(&dep) {
+                Some(runner) => run_target_of(sim, &dep).map(|targ| (runner, targ)),
+                Option::None => run_for_dependency(sim, &dep),
+            };
+        }
+        // without a greed mark anywhere the greed run has to come first
+        Val::Target(Target::Greedier) if runner_for(sim, Target::Greedier).is_none() => {
+            return run_for(sim, &Val::Target(Target::UltraGreed));
+        }
+        Val::Target(targ) => *targ,
+        Val::Achievement(_) => return run_for_dependency(sim, &dep),
+        Val::Mom(_) => Target::Mom,
+    };
+
+    runner_for(sim, target).map(|ch| (ch, target))
+}
+
+// the target a run has to beat to achieve what isn't a character in `dep`
+fn run_target_of(sim: &Unlocks, dep: &Dependency) -> Option<Target> {
+    use DependencyValue as Val;
+
+    let vals = match dep {
+        Dependency::None => return Option::None,
+        Dependency::Singular(val) => vec![val.clone()],
+        Dependency::Sum(vals) | Dependency::Product(vals) => vals.clone(),
+    };
+
+    vals.iter().find_map(|val| match val {
+        Val::Character(_) => Option::None,
+        Val::Target(targ) => Some(*targ),
+        Val::Mom(_) => Some(Target::Mom),
+        Val::Achievement(_) => run_target_of(sim, &sim.ruleset().depends_on(val)),
+    })
+}
+
+// characters are unlocked by whatever unlocks their dependencies, so that run is repeated
+fn run_for_dependency(sim: &Unlocks, dep: &Dependency) -> Option<(Character, Target)> {
+    match dep {
+        Dependency::None => None,
+        Dependency::Singular(val) => run_for(sim, val),
+        Dependency::Sum(vals) | Dependency::Product(vals) => vals
+            .iter()
+            .filter(|val| !matches!(val, DependencyValue::Character(_)))
+            .find_map(|val| run_for(sim, val)),
+    }
+}
+
+// the character that has to play the run, for dependencies on a character and something a run
+// achieves, like Lazarus and Mom's Heart for Bethany or the closet for tainted characters
+fn runner_of(dep: &Dependency) -> Option<Character> {
+    match dep {
+        Dependency::Product(vals)
+            if vals.iter().any(|val| !matches!(val, DependencyValue::Character(_))) =>
+        {
+            vals.iter().find_map(|val| match val {
+                DependencyValue::Character(ch) => Some(*ch),
+                _ => None,
+            })
+        }
+        _ => None,
+    }
+}
+
+// an unlocked character that can reach the target, preferring one still missing the mark
+fn runner_for(sim: &Unlocks, target: Target) -> Option<Character> {
+    let runners: Vec<Character> = Character::iter()
+        .filter(|ch| sim.unlocked_chars().contains(ch))
+        .filter(|ch| {
+            !matches!(ch, Character::Lost | Character::TaintedLost)
+                || sim.is_achievement_unlocked(Achievement::HolyMantle)
+        })
+        // greedier is only open to characters with the greed mark
+        .filter(|ch| {
+            target != Target::Greedier || sim.has_mark(ch, Target::UltraGreed, Difficulty::Normal)
+        })
+        .collect();
+
+    runners
+        .iter()
+        .find(|ch| !sim.has_mark(ch, target, Difficulty::Normal))
+        .or(runners.first())
+        .copied()
+}
+
+// everything winning `targets` with `ch` would unlock, in the order it gets unlocked
+pub fn unlocks_of_run(unlocks: &Unlocks, ch: Character, targets: &HashSet<Target>) -> Vec<String> {
+    let mut sim = unlocks.clone();
+    let mut targets: Vec<Target> = targets.iter().copied().collect();
+    targets.sort_by_key(|targ| *targ as u32);
+
+    targets
+        .into_iter()
+        .flat_map(|targ| apply_run(&mut sim, ch, targ))
+        .collect()
+}
+
+// marks the run as won and unlocks everything it leads to, returns what got unlocked
+fn apply_run(sim: &mut Unlocks, ch: Character, target: Target) -> Vec<String> {
+    use DependencyValue as Val;
+
+    let beaten = Target::with_passed_through(&HashSet::from([target]));
+    let mut unlocked = Vec::new();
+    // what the run did, nothing gets unlocked by runs that don't do what it depends on
+    let mut achieved = Vec::new();
+
+    let mut targets: Vec<Target> = beaten.iter().copied().collect();
+    targets.sort_by_key(|targ| *targ as u32);
+    for targ in targets {
+        achieved.push(Val::Target(targ));
+        if !sim.unlocked_targets().contains(&targ) {
+            sim.add_unlocked_targets(HashSet::from([targ]));
+            unlocked.push(Val::Target(targ).to_string());
+        }
+    }
+
+    // beating Mom is what opens up Mom's Heart
+    if beaten.contains(&Target::Mom) {
+        achieved.push(Val::Mom(Mom));
+        if !sim.unlocked_targets().contains(&Target::Heart) {
+            sim.add_unlocked_targets(HashSet::from([Target::Heart]));
+            unlocked.push(Val::Target(Target::Heart).to_string());
+        }
+    }
+
+    // targets are only unlocked by beating them, everything else by a run doing what it depends
+    // on, and unlocking something can unlock what depends on it in turn
+    loop {
+        let new_vals: Vec<Val> = Achievement::TRACKED
+            .into_iter()
+            .map(Val::Achievement)
+            .chain(Character::iter().map(Val::Character))
+            .filter(|val| !sim.is_dependency_val_unlocked(val))
+            .filter(|val| {
+                let dep = sim.ruleset().depends_on(val);
+                sim.is_unlocked_now(&dep) && is_achieved_by_run(&dep, ch, &achieved)
+            })
+            .collect();
+        if new_vals.is_empty() {
+            break;
+        }
+
+        for val in new_vals {
+            match &val {
+                Val::Character(new_ch) => {
+                    sim.add_unlocked_chars(HashSet::from([*new_ch]));
+                }
+                Val::Achievement(achievement) => {
+                    sim.set_achievement_unlocked(*achievement, true);
+                }
+                _ => {}
+            }
+            unlocked.push(val.to_string());
+            achieved.push(val);
+        }
+    }
+
+    sim.add_marks(ch, beaten, Difficulty::Normal);
+    unlocked
+}
+
+// a run by `ch` that achieved `achieved` does what `dep` needs, the character of a dependency
+// with a runner has to be the one playing
+fn is_achieved_by_run(dep: &Dependency, ch: Character, achieved: &[DependencyValue]) -> bool {
+    match (dep, runner_of(dep)) {
+        (Dependency::Product(vals), Some(_)) => {
+            vals.iter().all(|val| match val {
+                DependencyValue::Character(runner) => *runner == ch,
+                _ => true,
+            }) && vals
+                .iter()
+                .any(|val| !matches!(val, DependencyValue::Character(_)) && achieved.contains(val))
+        }
+        _ => depends_on_any(dep, achieved),
+    }
+}
+
+fn depends_on_any(dep: &Dependency, vals: &[DependencyValue]) -> bool {
+    match dep {
+        Dependency::None => false,
+        Dependency::Singular(val) => vals.contains(val),
+        Dependency::Sum(dep_vals) | Dependency::Product(dep_vals) => {
+            dep_vals.iter().any(|val| vals.contains(val))
+        }
+    }
+}
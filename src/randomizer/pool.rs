@@ -1,24 +1,96 @@
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::challenges::Challenge;
 use crate::randomizer::characters::Character;
-use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency, Mantle};
+use crate::randomizer::coop::{CoopPick, CoopTargets};
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::donations::Donation;
+use crate::randomizer::history::{Outcome, RerollPeriod, RollRecord, MAX_HISTORY};
+use crate::randomizer::ruleset::Ruleset;
 use crate::randomizer::targets::Target;
-use crate::toml_parse::savefile::{General, Marks, Savefile};
+use crate::randomizer::trace::{Trace, TraceStep};
+use crate::randomizer::weighting::Weighting;
+use crate::toml_parse::savefile::{General, HistoryEntry, Marks, Savefile};
 use rand::seq::SliceRandom;
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
 use std::collections::{HashMap, HashSet};
 use strum::IntoEnumIterator;
 
-#[derive(Debug)]
+// characters and targets a roll must not contain
+#[derive(Debug, Default)]
+pub struct Exclusion {
+    characters: HashSet<Character>,
+    targets: HashSet<Target>,
+    // rolled again on purpose, so the no-repeat rule doesn't apply to it
+    kept_character: Option<Character>,
+}
+
+impl Exclusion {
+    pub fn new(characters: HashSet<Character>, targets: HashSet<Target>) -> Self {
+        Self {
+            characters,
+            targets,
+            kept_character: None,
+        }
+    }
+
+    pub fn set_kept_character(&mut self, ch: Character) -> &mut Self {
+        self.kept_character = Some(ch);
+        self
+    }
+
+    fn apply(
+        &self,
+        pool: HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
+    ) -> HashMap<Character, HashSet<Target>> {
+        if !self.characters.is_empty() || !self.targets.is_empty() {
+            let mut characters: Vec<Character> = self.characters.iter().copied().collect();
+            characters.sort_by_key(|ch| *ch as u32);
+            let mut targets: Vec<Target> = self.targets.iter().copied().collect();
+            targets.sort_by_key(|targ| *targ as u32);
+            trace.push(TraceStep::Excluded {
+                characters,
+                targets,
+            });
+        }
+
+        pool.into_iter()
+            .filter(|(ch, _)| !self.characters.contains(ch))
+            .map(|(ch, targets)| {
+                let targets: HashSet<Target> = targets.difference(&self.targets).copied().collect();
+                (ch, targets)
+            })
+            .filter(|(_, targets)| !targets.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Unlocks {
-    marks: HashMap<Character, HashSet<Target>>,
+    marks: HashMap<Character, HashSet<(Target, Difficulty)>>,
     unlocked_chars: HashSet<Character>,
     unlocked_targets: HashSet<Target>,
-    is_mantle_unlocked: bool,
-    is_it_lives_unlocked: bool,
-    is_polaroid_unlocked: bool,
-    is_negative_unlocked: bool,
+    achievements: HashSet<Achievement>,
+    completed_challenges: HashSet<Challenge>,
+    completed_donations: HashSet<Donation>,
     boss_rush_chance: f32,
     hush_chance: f32,
     roll_boss_rush_on_alt: bool,
+    weighting: Weighting,
+    manual_weights: HashMap<Character, f32>,
+    history: Vec<RollRecord>,
+    no_repeat_char_within: usize,
+    no_repeat_pair: bool,
+    max_rerolls: usize,
+    reroll_period: RerollPeriod,
+    ruleset: Ruleset,
+    // marks only done on normal are left to do again on hard
+    prefer_hard: bool,
+    coop_targets: CoopTargets,
+    // marking a target also marks the targets passed on the way to it
+    imply_marks: bool,
 }
 
 impl Default for Unlocks {
@@ -27,63 +99,116 @@ impl Default for Unlocks {
             marks: HashMap::new(),
             unlocked_chars: HashSet::from([Character::Isaac]),
             unlocked_targets: HashSet::from([Target::Mom, Target::UltraGreed, Target::BossRush]),
-            is_mantle_unlocked: false,
-            is_it_lives_unlocked: false,
-            is_polaroid_unlocked: false,
-            is_negative_unlocked: false,
+            achievements: HashSet::new(),
+            completed_challenges: HashSet::new(),
+            completed_donations: HashSet::new(),
             boss_rush_chance: 1.0,
             hush_chance: 1.0,
             roll_boss_rush_on_alt: true,
+            weighting: Weighting::Uniform,
+            manual_weights: HashMap::new(),
+            history: Vec::new(),
+            no_repeat_char_within: 0,
+            no_repeat_pair: false,
+            max_rerolls: 0,
+            reroll_period: RerollPeriod::Session,
+            ruleset: Ruleset::builtin(),
+            prefer_hard: false,
+            coop_targets: CoopTargets::Any,
+            imply_marks: true,
         }
     }
 }
 
 impl Unlocks {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        marks: HashMap<Character, HashSet<Target>>,
+        marks: HashMap<Character, HashSet<(Target, Difficulty)>>,
         unlocked_chars: HashSet<Character>,
         unlocked_targets: HashSet<Target>,
-        is_mantle_unlocked: bool,
-        is_it_lives_unlocked: bool,
-        is_polaroid_unlocked: bool,
-        is_negative_unlocked: bool,
+        achievements: HashSet<Achievement>,
+        completed_challenges: HashSet<Challenge>,
+        completed_donations: HashSet<Donation>,
         boss_rush_chance: f32,
         hush_chance: f32,
         roll_boss_rush_on_alt: bool,
+        weighting: Weighting,
+        manual_weights: HashMap<Character, f32>,
+        history: Vec<RollRecord>,
+        no_repeat_char_within: usize,
+        no_repeat_pair: bool,
+        max_rerolls: usize,
+        reroll_period: RerollPeriod,
+        prefer_hard: bool,
+        coop_targets: CoopTargets,
+        imply_marks: bool,
     ) -> Self {
         Self {
             marks,
             unlocked_chars,
             unlocked_targets,
-            is_mantle_unlocked,
-            is_it_lives_unlocked,
-            is_polaroid_unlocked,
-            is_negative_unlocked,
+            achievements,
+            completed_challenges,
+            completed_donations,
             boss_rush_chance,
             hush_chance,
             roll_boss_rush_on_alt,
+            weighting,
+            manual_weights,
+            history,
+            no_repeat_char_within,
+            no_repeat_pair,
+            max_rerolls,
+            reroll_period,
+            ruleset: Ruleset::builtin(),
+            prefer_hard,
+            coop_targets,
+            imply_marks,
         }
     }
 
-    pub fn set_marks(&mut self, ch: Character, marks: HashSet<Target>) -> &mut Self {
+    pub fn set_marks(&mut self, ch: Character, marks: HashSet<(Target, Difficulty)>) -> &mut Self {
         if !self.unlocked_chars.contains(&ch) {
             self.unlocked_chars.insert(ch);
         }
-        marks.iter().for_each(|targ| {
-            if !self.unlocked_targets.contains(&targ) {
-                self.unlocked_targets.insert(targ.clone());
+        marks.iter().for_each(|(targ, _)| {
+            if !self.unlocked_targets.contains(targ) {
+                self.unlocked_targets.insert(*targ);
             }
         });
 
-        self.marks.insert(ch, marks);
+        self.marks.insert(ch, with_normal_marks(marks));
         self
     }
 
-    pub fn add_marks(&mut self, ch: Character, marks: HashSet<Target>) -> &mut Self {
+    // keeps the marks the way a savefile has them, without unlocking what they are on like
+    // set_marks does. validate lists the marks on something locked
+    pub fn set_saved_marks(
+        &mut self,
+        ch: Character,
+        marks: HashSet<(Target, Difficulty)>,
+    ) -> &mut Self {
+        self.marks.insert(ch, with_normal_marks(marks));
+        self
+    }
+
+    pub fn add_marks(
+        &mut self,
+        ch: Character,
+        marks: HashSet<Target>,
+        difficulty: Difficulty,
+    ) -> &mut Self {
+        let marks = if self.imply_marks {
+            Target::with_passed_through(&marks)
+        } else {
+            marks
+        };
+        let marks = with_normal_marks(marks.into_iter().map(|targ| (targ, difficulty)).collect());
+
         match self.marks.get_mut(&ch) {
             Some(targs) => {
-                for targ in marks {
-                    targs.insert(targ);
+                for mark in marks {
+                    targs.insert(mark);
                 }
             }
             None => {
@@ -94,14 +219,13 @@ impl Unlocks {
         self
     }
 
+    // removes the marks on every difficulty
     pub fn remove_marks(&mut self, ch: &Character, marks: &HashSet<Target>) -> &mut Self {
-        if let Some(targs) = self.marks.get_mut(&ch) {
-            for targ in marks {
-                targs.remove(targ);
-            }
+        if let Some(targs) = self.marks.get_mut(ch) {
+            targs.retain(|(targ, _)| !marks.contains(targ));
 
-            if self.marks.get(&ch).unwrap().is_empty() {
-                self.marks.remove(&ch);
+            if self.marks.get(ch).unwrap().is_empty() {
+                self.marks.remove(ch);
             }
         } else {
             return self
@@ -111,23 +235,14 @@ impl Unlocks {
     }
 
     pub fn remove_all_marks(&mut self, ch: &Character) -> &mut Self {
-        self.marks.remove(&ch);
+        self.marks.remove(ch);
         self
     }
 
+    // characters with marks stay unlocked, like set_marks unlocks them
     pub fn set_unlocked_chars(&mut self, chars: HashSet<Character>) -> &mut Self {
         self.unlocked_chars = chars;
-
-        let mut to_remove: Vec<Character> = Vec::new();
-        for (ch, _) in &self.marks {
-            if !self.unlocked_chars.contains(ch) {
-                to_remove.push(*ch);
-            }
-        }
-
-        for ch_to_remove in to_remove {
-            self.unlocked_chars.remove(&ch_to_remove);
-        }
+        self.unlocked_chars.extend(self.marks.keys().copied());
 
         self
     }
@@ -142,8 +257,8 @@ impl Unlocks {
 
     pub fn remove_unlocked_chars(&mut self, chars: &HashSet<Character>) -> &mut Self {
         for ch in chars {
-            if self.unlocked_chars.remove(&ch) {
-                self.marks.remove(&ch);
+            if self.unlocked_chars.remove(ch) {
+                self.marks.remove(ch);
             }
         }
 
@@ -153,17 +268,17 @@ impl Unlocks {
     pub fn set_unlocked_targets(&mut self, targets: HashSet<Target>) -> &mut Self {
         self.unlocked_targets = targets;
 
-        for (_, targets) in &mut self.marks {
-            let mut to_remove: Vec<Target> = Vec::new();
+        for targets in self.marks.values_mut() {
+            let mut to_remove: Vec<(Target, Difficulty)> = Vec::new();
 
-            for targ in targets.iter() {
-                if !self.unlocked_targets.contains(&targ) {
-                    to_remove.push(*targ);
+            for mark in targets.iter() {
+                if !self.unlocked_targets.contains(&mark.0) {
+                    to_remove.push(*mark);
                 }
             }
 
-            for targ_to_remove in to_remove {
-                targets.remove(&targ_to_remove);
+            for mark_to_remove in to_remove {
+                targets.remove(&mark_to_remove);
             }
         }
 
@@ -180,9 +295,9 @@ impl Unlocks {
 
     pub fn remove_unlocked_targets(&mut self, targs: &HashSet<Target>) -> &mut Self {
         for targ in targs {
-            if self.unlocked_targets.remove(&targ) {
-                for (_, marked_targets) in &mut self.marks {
-                    marked_targets.remove(&targ);
+            if self.unlocked_targets.remove(targ) {
+                for marked_targets in self.marks.values_mut() {
+                    marked_targets.retain(|(marked, _)| marked != targ);
                 }
             }
         }
@@ -190,23 +305,30 @@ impl Unlocks {
         self
     }
 
-    pub fn set_mantle_unlocked(&mut self, is_unlocked: bool) -> &mut Self {
-        self.is_mantle_unlocked = is_unlocked;
-        self
-    }
-
-    pub fn set_it_lives_unlocked(&mut self, is_unlocked: bool) -> &mut Self {
-        self.is_it_lives_unlocked = is_unlocked;
+    pub fn set_achievement_unlocked(&mut self, achievement: Achievement, is_unlocked: bool) -> &mut Self {
+        if is_unlocked {
+            self.achievements.insert(achievement);
+        } else {
+            self.achievements.remove(&achievement);
+        }
         self
     }
 
-    pub fn set_polaroid_unlocked(&mut self, is_unlocked: bool) -> &mut Self {
-        self.is_polaroid_unlocked = is_unlocked;
+    pub fn set_challenge_completed(&mut self, challenge: Challenge, is_completed: bool) -> &mut Self {
+        if is_completed {
+            self.completed_challenges.insert(challenge);
+        } else {
+            self.completed_challenges.remove(&challenge);
+        }
         self
     }
 
-    pub fn set_negative_unlocked(&mut self, is_unlocked: bool) -> &mut Self {
-        self.is_negative_unlocked = is_unlocked;
+    pub fn set_donation_completed(&mut self, donation: Donation, is_completed: bool) -> &mut Self {
+        if is_completed {
+            self.completed_donations.insert(donation);
+        } else {
+            self.completed_donations.remove(&donation);
+        }
         self
     }
 
@@ -225,28 +347,309 @@ impl Unlocks {
         self
     }
 
+    pub fn set_weighting(&mut self, weighting: Weighting) -> &mut Self {
+        self.weighting = weighting;
+        self
+    }
+
+    pub fn set_manual_weight(&mut self, ch: Character, weight: f32) -> &mut Self {
+        self.manual_weights.insert(ch, weight);
+        self
+    }
+
+    pub fn set_no_repeat_char_within(&mut self, rolls: usize) -> &mut Self {
+        self.no_repeat_char_within = rolls;
+        self
+    }
+
+    pub fn set_no_repeat_pair(&mut self, is_enabled: bool) -> &mut Self {
+        self.no_repeat_pair = is_enabled;
+        self
+    }
+
+    pub fn set_max_rerolls(&mut self, max_rerolls: usize) -> &mut Self {
+        self.max_rerolls = max_rerolls;
+        self
+    }
+
+    pub fn set_reroll_period(&mut self, period: RerollPeriod) -> &mut Self {
+        self.reroll_period = period;
+        self
+    }
+
+    pub fn set_prefer_hard(&mut self, is_enabled: bool) -> &mut Self {
+        self.prefer_hard = is_enabled;
+        self
+    }
+
+    pub fn set_coop_targets(&mut self, coop_targets: CoopTargets) -> &mut Self {
+        self.coop_targets = coop_targets;
+        self
+    }
+
+    pub fn set_imply_marks(&mut self, is_enabled: bool) -> &mut Self {
+        self.imply_marks = is_enabled;
+        self
+    }
+
+    pub fn set_ruleset(&mut self, ruleset: Ruleset) -> &mut Self {
+        self.ruleset = ruleset;
+        self
+    }
+
+    pub fn push_history(&mut self, record: RollRecord) -> &mut Self {
+        self.history.push(record);
+        if self.history.len() > MAX_HISTORY {
+            self.history.drain(..self.history.len() - MAX_HISTORY);
+        }
+        self
+    }
+
+    pub fn record_roll(&mut self, ch: Character, targets: HashSet<Target>) -> &mut Self {
+        self.push_history(RollRecord::now(ch, targets, Outcome::Rolled))
+    }
+
+    pub fn set_pending_roll_outcome(&mut self, outcome: Outcome) -> &mut Self {
+        if let Some(record) = self.history.last_mut() {
+            if record.outcome() == Outcome::Rolled {
+                record.set_outcome(outcome);
+            }
+        }
+        self
+    }
+
+    // copies everything that isn't game progress (options and roll history)
+    pub fn copy_settings_from(&mut self, other: &Unlocks) -> &mut Self {
+        self.boss_rush_chance = other.boss_rush_chance;
+        self.hush_chance = other.hush_chance;
+        self.roll_boss_rush_on_alt = other.roll_boss_rush_on_alt;
+        self.weighting = other.weighting;
+        self.manual_weights = other.manual_weights.clone();
+        self.history = other.history.clone();
+        self.no_repeat_char_within = other.no_repeat_char_within;
+        self.no_repeat_pair = other.no_repeat_pair;
+        self.max_rerolls = other.max_rerolls;
+        self.reroll_period = other.reroll_period;
+        self.ruleset = other.ruleset.clone();
+        self.prefer_hard = other.prefer_hard;
+        self.coop_targets = other.coop_targets;
+        self.imply_marks = other.imply_marks;
+        self
+    }
+
+    pub fn marks(&self) -> &HashMap<Character, HashSet<(Target, Difficulty)>> {
+        &self.marks
+    }
+
+    pub fn has_mark(&self, ch: &Character, targ: Target, difficulty: Difficulty) -> bool {
+        self.marks
+            .get(ch)
+            .is_some_and(|marks| marks.contains(&(targ, difficulty)))
+    }
+
+    pub fn prefer_hard(&self) -> bool {
+        self.prefer_hard
+    }
+
+    // a mark done on normal that is still missing on hard
+    pub fn is_hard_leftover(&self, ch: &Character, targ: Target) -> bool {
+        self.has_mark(ch, targ, Difficulty::Normal) && !self.has_mark(ch, targ, Difficulty::Hard)
+    }
+
+    // a roll to play on hard, because prefer_hard rolled marks that are only done on normal
+    pub fn is_hard_roll(&self, chars: &[Character], targets: &HashSet<Target>) -> bool {
+        self.prefer_hard
+            && chars
+                .iter()
+                .any(|ch| targets.iter().any(|targ| self.is_hard_leftover(ch, *targ)))
+    }
+
+    pub fn coop_targets(&self) -> CoopTargets {
+        self.coop_targets
+    }
+
+    pub fn imply_marks(&self) -> bool {
+        self.imply_marks
+    }
+
+    pub fn unlocked_chars(&self) -> &HashSet<Character> {
+        &self.unlocked_chars
+    }
+
+    pub fn unlocked_targets(&self) -> &HashSet<Target> {
+        &self.unlocked_targets
+    }
+
+    pub fn achievements(&self) -> &HashSet<Achievement> {
+        &self.achievements
+    }
+
+    pub fn is_achievement_unlocked(&self, achievement: Achievement) -> bool {
+        self.achievements.contains(&achievement)
+    }
+
+    pub fn completed_challenges(&self) -> &HashSet<Challenge> {
+        &self.completed_challenges
+    }
+
+    pub fn completed_donations(&self) -> &HashSet<Donation> {
+        &self.completed_donations
+    }
+
+    pub fn is_challenge_unlocked(&self, challenge: Challenge) -> bool {
+        self.is_unlocked_now(&challenge.depends_on())
+    }
+
+    // an uncompleted challenge that can be played right now
+    pub fn get_random_challenge<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Challenge> {
+        let challenges: Vec<Challenge> = Challenge::iter()
+            .filter(|challenge| !self.completed_challenges.contains(challenge))
+            .filter(|challenge| self.is_challenge_unlocked(*challenge))
+            .collect();
+
+        challenges.choose(rng).copied()
+    }
+
+    pub fn ruleset(&self) -> &Ruleset {
+        &self.ruleset
+    }
+
+    pub fn history(&self) -> &Vec<RollRecord> {
+        &self.history
+    }
+
+    // the last roll if nothing was reported for it yet
+    pub fn pending_roll(&self) -> Option<&RollRecord> {
+        self.history
+            .last()
+            .filter(|record| record.outcome() == Outcome::Rolled)
+    }
+
+    // a reroll is the roll recorded right after a vetoed one, so it is counted at the time it was
+    // made and not at the time of the roll it replaced
+    pub fn rerolls_used(&self, now: u64) -> usize {
+        let session_start = self
+            .history
+            .iter()
+            .rposition(|record| matches!(record.outcome(), Outcome::Done | Outcome::Failed))
+            .map(|pos| pos + 1)
+            .unwrap_or(0);
+
+        self.history
+            .windows(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0].outcome() == Outcome::Vetoed)
+            .filter(|(i, pair)| match self.reroll_period {
+                RerollPeriod::Session => i + 1 >= session_start,
+                // since midnight UTC
+                RerollPeriod::Day => pair[1].timestamp() / 86400 == now / 86400,
+            })
+            .count()
+    }
+
+    // None when there is no limit
+    pub fn rerolls_left(&self, now: u64) -> Option<usize> {
+        if self.max_rerolls == 0 {
+            return None;
+        }
+
+        Some(self.max_rerolls.saturating_sub(self.rerolls_used(now)))
+    }
+
     pub fn set_everything_unlocked(&mut self) {
         for ch in Character::iter() {
             let targs = Target::iter()
                 .filter(|targ| self.is_target_significant(targ))
+                .map(|targ| (targ, Difficulty::Hard))
                 .collect();
             self.set_marks(ch, targs);
         }
-        self.set_mantle_unlocked(true)
-            .set_it_lives_unlocked(true)
-            .set_polaroid_unlocked(true)
-            .set_negative_unlocked(true);
+        self.achievements = Achievement::iter().collect();
     }
 
     pub fn get_random_pick(&self) -> Option<(Character, HashSet<Target>)> {
+        self.get_random_pick_with_rng(&mut rand::thread_rng())
+    }
+
+    // ChaCha8 gives the same numbers on every platform and rand version, so seeds can be shared
+    pub fn get_seeded_random_pick(&self, seed: u64) -> Option<(Character, HashSet<Target>)> {
+        self.get_random_pick_with_rng(&mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    pub fn get_random_pick_with_rng<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> Option<(Character, HashSet<Target>)> {
+        self.get_random_pick_excluding(rng, &Exclusion::default())
+    }
+
+    pub fn get_random_pick_excluding<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        exclusion: &Exclusion,
+    ) -> Option<(Character, HashSet<Target>)> {
+        self.get_traced_random_pick(rng, exclusion).0
+    }
+
+    // same as get_random_pick_excluding, but also returns every decision made for the pick
+    pub fn get_traced_random_pick<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        exclusion: &Exclusion,
+    ) -> (Option<(Character, HashSet<Target>)>, Trace) {
+        let mut trace = Trace::default();
+        let pool = self.get_pool(exclusion, &mut trace);
+
+        let pick = self.roll_pool(pool, rng, &mut trace);
+        (pick, trace)
+    }
+
+    pub fn get_coop_pick(&self, players: usize) -> Option<CoopPick> {
+        self.get_coop_pick_with_rng(players, &mut rand::thread_rng())
+    }
+
+    pub fn get_seeded_coop_pick(&self, players: usize, seed: u64) -> Option<CoopPick> {
+        self.get_coop_pick_with_rng(players, &mut ChaCha8Rng::seed_from_u64(seed))
+    }
+
+    pub fn get_coop_pick_with_rng<R: Rng + ?Sized>(
+        &self,
+        players: usize,
+        rng: &mut R,
+    ) -> Option<CoopPick> {
+        self.get_traced_coop_pick(players, rng, &Exclusion::default()).0
+    }
+
+    // rolls `players` characters that go for the same targets
+    pub fn get_traced_coop_pick<R: Rng + ?Sized>(
+        &self,
+        players: usize,
+        rng: &mut R,
+        exclusion: &Exclusion,
+    ) -> (Option<CoopPick>, Trace) {
+        let mut trace = Trace::default();
+        let pool = self.get_pool(exclusion, &mut trace);
+
+        let pick = self.roll_coop_pool(pool, players, exclusion, rng, &mut trace);
+        (pick, trace)
+    }
+
+    // every character that can be rolled with the targets it can be rolled with
+    fn get_pool(
+        &self,
+        exclusion: &Exclusion,
+        trace: &mut Trace,
+    ) -> HashMap<Character, HashSet<Target>> {
         if self.is_everything_unlocked() {
-            return self.roll_pool(Self::everything_pool());
+            trace.push(TraceStep::EverythingCompleted);
+            let pool = exclusion.apply(Self::everything_pool(), trace);
+            return self.apply_history_rules(pool, exclusion, trace);
         }
 
         let mut not_finished: HashSet<Character> = HashSet::new();
         for ch in &self.unlocked_chars {
-            if !self.is_char_completed(&ch) {
-                not_finished.insert(ch.clone());
+            if !self.is_char_completed(ch) {
+                not_finished.insert(*ch);
             }
         }
         let not_finished = not_finished;
@@ -260,12 +663,113 @@ impl Unlocks {
             }
         }
 
-        if pool.is_empty() {
-            pool = self.get_additional_targets();
+        let is_fallback = pool.is_empty();
+        if is_fallback {
+            trace.push(TraceStep::DependencyFallback);
+            pool = self.get_additional_targets(trace);
+        } else {
+            let mut chars: Vec<Character> = pool.keys().copied().collect();
+            chars.sort_by_key(|ch| *ch as u32);
+            trace.push(TraceStep::UnfinishedPool(chars));
         }
-        let pool = pool;
+        let mut pool = exclusion.apply(pool, trace);
+        if !is_fallback {
+            pool = self.apply_prefer_hard(pool, trace);
+        }
+        self.apply_history_rules(pool, exclusion, trace)
+    }
 
-        self.roll_pool(pool)
+    // with prefer_hard the marks only done on normal are rolled first, to be done again on hard,
+    // the other remaining marks are rolled once none of those are left
+    fn apply_prefer_hard(
+        &self,
+        pool: HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
+    ) -> HashMap<Character, HashSet<Target>> {
+        if !self.prefer_hard {
+            return pool;
+        }
+
+        let leftovers: HashMap<Character, HashSet<Target>> = pool
+            .iter()
+            .map(|(ch, targets)| {
+                let targets: HashSet<Target> = targets
+                    .iter()
+                    .filter(|targ| self.is_hard_leftover(ch, **targ))
+                    .copied()
+                    .collect();
+                (*ch, targets)
+            })
+            .filter(|(_, targets)| !targets.is_empty())
+            .collect();
+        if leftovers.is_empty() {
+            return pool;
+        }
+
+        let mut chars: Vec<Character> = leftovers.keys().copied().collect();
+        chars.sort_by_key(|ch| *ch as u32);
+        trace.push(TraceStep::HardLeftovers(chars));
+        leftovers
+    }
+
+    // removes recently rolled characters and already rolled character+target pairs from the pool,
+    // the rules are dropped if nothing would be left to roll
+    fn apply_history_rules(
+        &self,
+        pool: HashMap<Character, HashSet<Target>>,
+        exclusion: &Exclusion,
+        trace: &mut Trace,
+    ) -> HashMap<Character, HashSet<Target>> {
+        let recent_chars: HashSet<Character> = self
+            .history
+            .iter()
+            .rev()
+            .take(self.no_repeat_char_within)
+            .map(|record| record.character())
+            .filter(|ch| exclusion.kept_character != Some(*ch))
+            .collect();
+
+        let mut filtered: HashMap<Character, HashSet<Target>> = HashMap::new();
+        for (ch, targets) in &pool {
+            if recent_chars.contains(ch) {
+                continue;
+            }
+
+            let mut targets = targets.clone();
+            // vetoed pairs stay in the pool so a veto can't skip a pair forever
+            if self.no_repeat_pair {
+                for record in self
+                    .history
+                    .iter()
+                    .filter(|record| record.character() == *ch)
+                    .filter(|record| record.outcome() != Outcome::Vetoed)
+                {
+                    targets.retain(|targ| !record.targets().contains(targ));
+                }
+            }
+
+            if !targets.is_empty() {
+                filtered.insert(*ch, targets);
+            }
+        }
+
+        if filtered.is_empty() {
+            if !pool.is_empty() {
+                trace.push(TraceStep::HistoryRulesDropped);
+            }
+            pool
+        } else {
+            let mut removed: Vec<Character> = pool
+                .iter()
+                .filter(|(ch, targets)| filtered.get(ch) != Some(targets))
+                .map(|(ch, _)| *ch)
+                .collect();
+            if !removed.is_empty() {
+                removed.sort_by_key(|ch| *ch as u32);
+                trace.push(TraceStep::HistoryRulesApplied(removed));
+            }
+            filtered
+        }
     }
 
     fn is_everything_unlocked(&self) -> bool {
@@ -279,10 +783,11 @@ impl Unlocks {
     }
 
     fn is_char_completed(&self, ch: &Character) -> bool {
-        match self.marks.get(&ch) {
+        match self.marks.get(ch) {
             Some(marks) => {
+                let difficulty = self.required_difficulty();
                 for targ in Target::iter().filter(|t| self.is_target_significant(t)) {
-                    if !marks.contains(&targ) {
+                    if !marks.contains(&(targ, difficulty)) {
                         return false;
                     }
                 }
@@ -292,6 +797,24 @@ impl Unlocks {
         true
     }
 
+    // difficulty a mark has to be done on to count as done when rolling
+    pub fn required_difficulty(&self) -> Difficulty {
+        if self.prefer_hard {
+            Difficulty::Hard
+        } else {
+            Difficulty::Normal
+        }
+    }
+
+    fn completed_targets(&self, marks: &HashSet<(Target, Difficulty)>) -> HashSet<Target> {
+        let difficulty = self.required_difficulty();
+        marks
+            .iter()
+            .filter(|(_, diff)| *diff == difficulty)
+            .map(|(targ, _)| *targ)
+            .collect()
+    }
+
     fn everything_pool() -> HashMap<Character, HashSet<Target>> {
         let mut pool = HashMap::new();
 
@@ -306,12 +829,14 @@ impl Unlocks {
     }
 
     fn unlocked_targets_for(&self, ch: &Character) -> HashSet<Target> {
-        if (ch == &Character::Lost || ch == &Character::TaintedLost) && !self.is_mantle_unlocked {
+        if (ch == &Character::Lost || ch == &Character::TaintedLost)
+            && !self.is_achievement_unlocked(Achievement::HolyMantle)
+        {
             return HashSet::new();
         }
 
-        let mut valid_targets: HashSet<Target> = match self.marks.get(&ch) {
-            Some(completed) => Target::get_remaining(completed)
+        let mut valid_targets: HashSet<Target> = match self.marks.get(ch) {
+            Some(marks) => Target::get_remaining(&self.completed_targets(marks))
                 .iter()
                 .filter(|rem| self.unlocked_targets.contains(rem) && self.is_target_significant(rem))
                 .cloned()
@@ -323,54 +848,35 @@ impl Unlocks {
                 .cloned()
                 .collect(),
         };
-        if valid_targets.contains(&Target::Mother) && !self.is_mantle_unlocked {
+        if valid_targets.contains(&Target::Mother)
+            && !self.is_achievement_unlocked(Achievement::HolyMantle)
+        {
             valid_targets.remove(&Target::Mother);
         }
+        // greedier needs the greed mark of the same character
+        if !self.has_mark(ch, Target::UltraGreed, Difficulty::Normal) {
+            valid_targets.remove(&Target::Greedier);
+        }
 
         valid_targets
     }
 
-    fn get_additional_targets(&self) -> HashMap<Character, HashSet<Target>> {
-        let unlockables = Self::get_unlockables();
+    fn get_additional_targets(&self, trace: &mut Trace) -> HashMap<Character, HashSet<Target>> {
         let mut targets = HashMap::new();
 
-        for unlockable in unlockables {
-            self.resolve_dependency(&unlockable, &mut targets);
+        for unlockable in self.ruleset.unlockables() {
+            let dep = Dependency::Singular(unlockable.clone());
+            self.resolve_dependency(&dep, &mut targets, trace);
         }
 
         targets
     }
 
-    fn get_unlockables() -> Vec<Dependency> {
-        let mut unlockables = vec![
-            Dependency::Singular(DependencyValue::Character(Character::Judas)),
-            Dependency::Singular(DependencyValue::Character(Character::BlueBaby)),
-            Dependency::Singular(DependencyValue::Character(Character::Keeper)),
-            Dependency::Singular(DependencyValue::Character(Character::Bethany)),
-            Dependency::Singular(DependencyValue::Character(Character::Apollyon)),
-            Dependency::Singular(DependencyValue::Mantle(Mantle)),
-            Dependency::Singular(DependencyValue::Target(Target::BlueBaby)),
-            Dependency::Singular(DependencyValue::Target(Target::Lamb)),
-            Dependency::Singular(DependencyValue::Target(Target::MegaSatan)),
-            Dependency::Singular(DependencyValue::Target(Target::Delirium)),
-            Dependency::Singular(DependencyValue::Target(Target::Beast)),
-            Dependency::Singular(DependencyValue::Target(Target::Mother)),
-            Dependency::Singular(DependencyValue::Target(Target::Hush)),
-        ];
-
-        for ch in Character::iter() {
-            if ch.is_tainted() {
-                unlockables.push(Dependency::Singular(DependencyValue::Character(ch)));
-            }
-        }
-
-        unlockables
-    }
-
     fn resolve_dependency(
         &self,
         dep: &Dependency,
         targets: &mut HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
     ) {
         use Dependency::*;
 
@@ -379,94 +885,149 @@ impl Unlocks {
         }
 
         if self.is_unlockable_now(dep) {
+            trace.push(TraceStep::Unlockable(dep.to_string()));
             match dep {
                 None => {}
-                Singular(val) => self.add_target_dependency_val(val, targets),
+                Singular(val) => self.add_target_dependency_val(val, targets, trace),
                 Sum(vals) => {
                     for val in vals {
                         if self.is_dependency_val_unlocked(val) {
-                            self.add_target_dependency_val(val, targets);
+                            self.add_target_dependency_val(val, targets, trace);
                         }
                     }
                 }
                 Product(vals) => {
                     for val in vals {
-                        self.add_target_dependency_val(val, targets);
+                        self.add_target_dependency_val(val, targets, trace);
                     }
                 }
             }
         } else {
+            trace.push(TraceStep::Locked(dep.to_string()));
             match dep {
                 None => {}
-                Singular(val) => self.resolve_dependency(&val.depends_on(), targets),
+                Singular(val) => self.resolve_dependency_val(val, targets, trace),
                 Sum(vals) | Product(vals) => {
                     for val in vals {
-                        self.resolve_dependency(&val.depends_on(), targets);
+                        self.resolve_dependency_val(val, targets, trace);
                     }
                 }
             }
         }
     }
 
+    fn resolve_dependency_val(
+        &self,
+        dep_val: &DependencyValue,
+        targets: &mut HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
+    ) {
+        match dep_val {
+            DependencyValue::Character(ch) if ch.is_tainted() => {
+                self.resolve_closet(*ch, targets, trace)
+            }
+            _ => self.resolve_dependency(&self.ruleset.depends_on(dep_val), targets, trace),
+        }
+    }
+
+    // a tainted character is found by its own character at Home, so Home is only added for that
+    // character once it and the rest the tainted one depends on are unlocked
+    fn resolve_closet(
+        &self,
+        tainted: Character,
+        targets: &mut HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
+    ) {
+        let normal = tainted.tainted_to_normal().unwrap();
+        let home = DependencyValue::Target(Target::Home);
+
+        let dep = self.ruleset.depends_on(&DependencyValue::Character(tainted));
+        let missing: Vec<DependencyValue> = match dep {
+            Dependency::None => Vec::new(),
+            Dependency::Singular(val) => vec![val],
+            Dependency::Sum(vals) | Dependency::Product(vals) => vals,
+        }
+        .into_iter()
+        .filter(|val| *val != home && !self.is_dependency_val_unlocked(val))
+        .collect();
+        if !missing.is_empty() {
+            for val in missing {
+                self.resolve_dependency(&Dependency::Singular(val), targets, trace);
+            }
+            return;
+        }
+
+        if !self.is_unlocked_now(&Dependency::Singular(home.clone()))
+            && !self.is_unlockable_now(&Dependency::Singular(home.clone()))
+        {
+            self.resolve_dependency(&Dependency::Singular(home), targets, trace);
+            return;
+        }
+
+        targets.entry(normal).or_default().insert(Target::Home);
+        trace.push(TraceStep::TargetAdded {
+            target: Target::Home,
+            characters: vec![normal],
+            unlocks: DependencyValue::Character(tainted).to_string(),
+        });
+    }
+
     fn add_target_dependency_val(
         &self,
         dep_val: &DependencyValue,
         targets: &mut HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
     ) {
         use DependencyValue;
 
         match dep_val {
             DependencyValue::Character(ch) => {
                 if ch.is_tainted() {
-                    match targets.get_mut(&ch.tainted_to_normal().unwrap()) {
-                        None => {
-                            targets.insert(
-                                ch.tainted_to_normal().unwrap(),
-                                HashSet::from([Target::Beast]),
-                            );
-                        }
-                        Some(set) => {
-                            set.insert(Target::Beast);
-                        }
-                    }
+                    self.resolve_closet(*ch, targets, trace);
                     return;
                 }
 
                 if ch == &Character::Bethany {
-                    let set = targets.entry(Character::Lazarus).or_insert(HashSet::new());
+                    let set = targets.entry(Character::Lazarus).or_default();
 
                     // add targets that have heart in their path (all but The Beast have it)
-                    let valid_targets = self.unlocked_targets_for(&ch);
+                    let valid_targets = self.unlocked_targets_for(ch);
 
-                    if valid_targets.is_empty() {
-                        set.insert(Target::Heart);
+                    let added: Vec<Target> = if valid_targets.is_empty() {
+                        vec![Target::Heart]
                     } else {
-                        set.extend(valid_targets.iter().filter(|&&targ| targ != Target::Beast));
+                        valid_targets
+                            .iter()
+                            .filter(|&&targ| targ != Target::Beast)
+                            .copied()
+                            .collect()
+                    };
+                    set.extend(added.iter());
+                    for targ in added {
+                        trace.push(TraceStep::TargetAdded {
+                            target: targ,
+                            characters: vec![Character::Lazarus],
+                            unlocks: dep_val.to_string(),
+                        });
                     }
                     return;
                 }
 
                 // other cases are present in their dependencies, you just need to add targets to
                 // all unlocked characters
-                self.resolve_dependency(&ch.depends_on(), targets);
+                self.resolve_dependency(&self.ruleset.depends_on(dep_val), targets, trace);
             }
             DependencyValue::Target(targ) => {
-                self.add_target_to_unlocked_chars(*targ, targets);
-            }
-            DependencyValue::Mantle(_) => {
-                self.add_target_to_unlocked_chars(Target::UltraGreed, targets);
+                self.add_target_to_unlocked_chars(*targ, dep_val, targets, trace);
             }
-            DependencyValue::ItLives(_) => {
-                self.add_target_to_unlocked_chars(Target::Heart, targets);
+            // achievements without rules can't be rolled for
+            DependencyValue::Achievement(achievement) => {
+                if let Some(target) = achievement.unlocked_by() {
+                    self.add_target_to_unlocked_chars(target, dep_val, targets, trace);
+                }
             }
             DependencyValue::Mom(_) => {
-                self.add_target_to_unlocked_chars(Target::Mom, targets);
-            }
-            DependencyValue::Polaroid(_) => {
-                self.add_target_to_unlocked_chars(Target::Isaac, targets);
-            }
-            DependencyValue::Negative(_) => {
-                self.add_target_to_unlocked_chars(Target::Satan, targets);
+                self.add_target_to_unlocked_chars(Target::Mom, dep_val, targets, trace);
             }
         }
     }
@@ -474,12 +1035,37 @@ impl Unlocks {
     fn add_target_to_unlocked_chars(
         &self,
         target: Target,
+        unlocks: &DependencyValue,
         targets: &mut HashMap<Character, HashSet<Target>>,
+        trace: &mut Trace,
     ) {
-        for ch in &self.unlocked_chars {
-            let set = targets.entry(*ch).or_insert(HashSet::new());
+        // greedier needs the greed mark of the same character
+        let mut characters: Vec<Character> = self
+            .unlocked_chars
+            .iter()
+            .filter(|ch| {
+                target != Target::Greedier
+                    || self.has_mark(ch, Target::UltraGreed, Difficulty::Normal)
+            })
+            .copied()
+            .collect();
+        // without a greed mark anywhere the greed run has to come first
+        if characters.is_empty() && target == Target::Greedier {
+            self.add_target_to_unlocked_chars(Target::UltraGreed, unlocks, targets, trace);
+            return;
+        }
+        characters.sort_by_key(|ch| *ch as u32);
+
+        for ch in &characters {
+            let set = targets.entry(*ch).or_default();
             set.insert(target);
         }
+
+        trace.push(TraceStep::TargetAdded {
+            target,
+            characters,
+            unlocks: unlocks.to_string(),
+        });
     }
 
     fn is_unlockable_now(&self, dep: &Dependency) -> bool {
@@ -508,11 +1094,46 @@ impl Unlocks {
     }
 
     fn is_dependency_val_unlockable(&self, dep_val: &DependencyValue) -> bool {
-        self.is_unlocked_now(&dep_val.depends_on())
+        self.is_unlocked_now(&self.dependency_of(dep_val))
             && !self.is_unlocked_now(&Dependency::Singular(dep_val.clone()))
     }
 
-    fn is_unlocked_now(&self, dep: &Dependency) -> bool {
+    // the closet is per character: Home counts as reached for a tainted character once its own
+    // character has a Home mark. Without one Home itself has to be unlocked
+    fn is_closet_open(&self, tainted: &Character) -> bool {
+        match tainted.tainted_to_normal() {
+            Some(normal) => self.has_mark(&normal, Target::Home, Difficulty::Normal),
+            None => false,
+        }
+    }
+
+    // what `val` depends on in the ruleset, without Home for tainted characters whose closet is open
+    pub fn dependency_of(&self, val: &DependencyValue) -> Dependency {
+        let dep = self.ruleset.depends_on(val);
+        let home = DependencyValue::Target(Target::Home);
+
+        match val {
+            DependencyValue::Character(ch) if ch.is_tainted() && self.is_closet_open(ch) => {
+                match dep {
+                    Dependency::Singular(val) if val == home => Dependency::None,
+                    Dependency::Sum(vals) if vals.contains(&home) => Dependency::None,
+                    Dependency::Product(vals) => {
+                        let mut vals: Vec<DependencyValue> =
+                            vals.into_iter().filter(|val| *val != home).collect();
+                        match vals.len() {
+                            0 => Dependency::None,
+                            1 => Dependency::Singular(vals.remove(0)),
+                            _ => Dependency::Product(vals),
+                        }
+                    }
+                    dep => dep,
+                }
+            }
+            _ => dep,
+        }
+    }
+
+    pub fn is_unlocked_now(&self, dep: &Dependency) -> bool {
         use Dependency::*;
 
         match dep {
@@ -537,74 +1158,132 @@ impl Unlocks {
         }
     }
 
-    fn is_dependency_val_unlocked(&self, dep_val: &DependencyValue) -> bool {
+    pub fn is_dependency_val_unlocked(&self, dep_val: &DependencyValue) -> bool {
         use DependencyValue::*;
         use crate::randomizer::targets::Target::Heart;
 
         match dep_val {
             Character(ch) => self.unlocked_chars.contains(ch),
             Target(targ) => self.unlocked_targets.contains(targ),
-            Mantle(_) => self.is_mantle_unlocked,
-            ItLives(_) => self.is_it_lives_unlocked,
+            Achievement(achievement) => self.achievements.contains(achievement),
             Mom(_) => self.unlocked_targets.contains(&Heart),
-            Polaroid(_) => self.is_polaroid_unlocked,
-            Negative(_) => self.is_negative_unlocked,
         }
     }
 
-    fn roll_pool(
+    fn roll_pool<R: Rng + ?Sized>(
         &self,
         pool: HashMap<Character, HashSet<Target>>,
+        rng: &mut R,
+        trace: &mut Trace,
     ) -> Option<(Character, HashSet<Target>)> {
-        let char_pool: Vec<Character> = pool.iter().map(|(ch, _)| -> Character { *ch }).collect();
+        // hash iteration order differs between runs, so pools are sorted to keep seeded rolls
+        // reproducible
+        let mut char_pool: Vec<Character> = pool.keys().copied().collect();
+        char_pool.sort_by_key(|ch| *ch as u32);
+        let char_pool = char_pool;
+
+        let rand_char = &self.pick_character(&char_pool, &pool, rng, trace)?;
+        let targets = self.roll_targets(pool.get(rand_char)?, &pool, rng, trace)?;
+
+        Some((*rand_char, targets))
+    }
+
+    fn pick_character<R: Rng + ?Sized>(
+        &self,
+        char_pool: &[Character],
+        pool: &HashMap<Character, HashSet<Target>>,
+        rng: &mut R,
+        trace: &mut Trace,
+    ) -> Option<Character> {
+        let char_weights: Vec<f32> = char_pool
+            .iter()
+            .map(|ch| match self.weighting {
+                Weighting::Manual => *self.manual_weights.get(ch).unwrap_or(&1.0),
+                _ => self.weighting.remaining_weight(pool[ch].len()),
+            })
+            .collect();
+
+        let rand_char = Weighting::choose(char_pool, &char_weights, rng)?;
+        trace.push(TraceStep::CharacterPicked {
+            character: rand_char,
+            candidates: char_pool.len(),
+            weighting: self.weighting,
+        });
 
-        let rand_char = char_pool.choose(&mut rand::thread_rng())?;
+        Some(rand_char)
+    }
+
+    // picks the targets of a run out of `candidates`, `pool` is only used for target weights
+    fn roll_targets<R: Rng + ?Sized>(
+        &self,
+        candidates: &HashSet<Target>,
+        pool: &HashMap<Character, HashSet<Target>>,
+        rng: &mut R,
+        trace: &mut Trace,
+    ) -> Option<HashSet<Target>> {
         let mut special_in_pool: HashSet<Target> = HashSet::new();
-        let target_pool: Vec<Target> = pool
-            .get(rand_char)?
+        let mut target_pool: Vec<Target> = candidates
             .iter()
-            .map(|targ| *targ)
+            .copied()
             .filter(|targ| match targ {
                 Target::BossRush | Target::Hush | Target::MegaSatan | Target::Delirium => {
                     special_in_pool.insert(*targ);
                     false
                 }
-                Target::UltraGreed | Target::Beast => {
+                Target::UltraGreed | Target::Greedier | Target::Beast | Target::Home => {
                     special_in_pool.insert(*targ);
                     true
                 }
-                _ => self.is_target_significant(&targ),
+                _ => self.is_target_significant(targ),
             })
             .collect();
+        target_pool.sort_by_key(|targ| *targ as u32);
+        let target_pool = target_pool;
         let special_in_pool = special_in_pool;
 
         let mut targets = HashSet::new();
 
-        let mut rng = rand::thread_rng();
-
         if !target_pool.is_empty() {
-            let rand_target = target_pool.choose(&mut rng)?;
-
-            if rand_target == &Target::UltraGreed {
+            // targets are weighted by how many characters in the pool still miss them
+            let target_weights: Vec<f32> = target_pool
+                .iter()
+                .map(|targ| {
+                    let missing = pool.values().filter(|targs| targs.contains(targ)).count();
+                    self.weighting.remaining_weight(missing)
+                })
+                .collect();
+            let rand_target = &Weighting::choose(&target_pool, &target_weights, rng)?;
+            trace.push(TraceStep::TargetPicked {
+                target: *rand_target,
+                candidates: target_pool.clone(),
+            });
+
+            if rand_target.is_greed_mode() {
+                trace.push(TraceStep::GreedModeAlone(*rand_target));
                 targets.insert(*rand_target);
-                return Some((*rand_char, targets));
+                return Some(targets);
             }
 
             if matches!(rand_target, Target::Lamb | Target::BlueBaby)
                 && special_in_pool.contains(&Target::MegaSatan)
             {
+                trace.push(TraceStep::MegaSatanAdded(*rand_target));
                 targets.insert(Target::MegaSatan);
             }
 
             targets.insert(*rand_target);
         } else if special_in_pool.contains(&Target::Delirium) {
+            trace.push(TraceStep::DeliriumWithHush);
             targets.insert(Target::Delirium);
             targets.insert(Target::Hush);
-            return Some((*rand_char, targets));
+            return Some(targets);
         }
 
         let should_roll_boss_rush = special_in_pool.contains(&Target::BossRush)
-            && if targets.contains(&Target::Beast) || targets.contains(&Target::Mother) {
+            && if targets.contains(&Target::Beast)
+                || targets.contains(&Target::Mother)
+                || targets.contains(&Target::Home)
+            {
                 self.roll_boss_rush_on_alt
             } else {
                 true
@@ -613,43 +1292,156 @@ impl Unlocks {
 
         let should_roll_hush = special_in_pool.contains(&Target::Hush)
             && !targets.contains(&Target::Beast)
-            && !targets.contains(&Target::Mother);
+            && !targets.contains(&Target::Mother)
+            && !targets.contains(&Target::Home);
 
-        if target_pool.len() == 1
-            || (target_pool.len() == 2 && special_in_pool.contains(&Target::UltraGreed))
-            || target_pool.is_empty()
+        if special_in_pool.contains(&Target::Hush) && !should_roll_hush {
+            trace.push(TraceStep::SkippedOnAltPath(Target::Hush));
+        }
+        if special_in_pool.contains(&Target::BossRush)
+            && !should_roll_boss_rush
+            && self.unlocked_targets.contains(&Target::Heart)
         {
+            trace.push(TraceStep::SkippedOnAltPath(Target::BossRush));
+        }
+
+        if target_pool.iter().filter(|targ| !targ.is_greed_mode()).count() <= 1 {
             if should_roll_hush {
+                trace.push(TraceStep::Forced(Target::Hush));
                 targets.insert(Target::Hush);
             }
             if should_roll_boss_rush {
+                trace.push(TraceStep::Forced(Target::BossRush));
                 targets.insert(Target::BossRush);
             }
         } else {
-            if should_roll_hush && (rng.gen::<f32>() <= self.hush_chance) {
-                targets.insert(Target::Hush);
+            if should_roll_hush {
+                self.roll_chance(Target::Hush, self.hush_chance, &mut targets, rng, trace);
             }
-            if should_roll_boss_rush && (rng.gen::<f32>() <= self.boss_rush_chance) {
-                targets.insert(Target::BossRush);
+            if should_roll_boss_rush {
+                self.roll_chance(Target::BossRush, self.boss_rush_chance, &mut targets, rng, trace);
             }
         }
 
         if targets.is_empty() {
+            trace.push(TraceStep::NothingRolled);
             None
         } else {
-            Some((*rand_char, targets))
+            Some(targets)
         }
     }
 
+    fn roll_coop_pool<R: Rng + ?Sized>(
+        &self,
+        pool: HashMap<Character, HashSet<Target>>,
+        players: usize,
+        exclusion: &Exclusion,
+        rng: &mut R,
+        trace: &mut Trace,
+    ) -> Option<CoopPick> {
+        let mut characters: Vec<Character> = Vec::new();
+        let mut shared: HashSet<Target> = HashSet::new();
+
+        while characters.len() < players {
+            // with All only characters that still share a target with the rolled ones can join
+            let mut char_pool: Vec<Character> = pool
+                .iter()
+                .filter(|(ch, _)| !characters.contains(ch))
+                .filter(|(_, targs)| {
+                    characters.is_empty()
+                        || self.coop_targets == CoopTargets::Any
+                        || !targs.is_disjoint(&shared)
+                })
+                .map(|(ch, _)| *ch)
+                .collect();
+            char_pool.sort_by_key(|ch| *ch as u32);
+
+            let Some(rand_char) = self.pick_character(&char_pool, &pool, rng, trace) else {
+                break;
+            };
+            shared = match (characters.is_empty(), self.coop_targets) {
+                (true, _) | (false, CoopTargets::Any) => shared.union(&pool[&rand_char]).copied().collect(),
+                (false, CoopTargets::All) => shared.intersection(&pool[&rand_char]).copied().collect(),
+            };
+            characters.push(rand_char);
+        }
+
+        if characters.is_empty() {
+            trace.push(TraceStep::NothingRolled);
+            return None;
+        }
+
+        // targets only have to be useful for one character, so anyone can fill the empty spots
+        if characters.len() < players && self.coop_targets == CoopTargets::Any {
+            let mut fillers: Vec<Character> = self
+                .unlocked_chars
+                .iter()
+                .filter(|ch| !characters.contains(ch) && !exclusion.characters.contains(ch))
+                .copied()
+                .collect();
+            fillers.sort_by_key(|ch| *ch as u32);
+            fillers.shuffle(rng);
+
+            for filler in fillers.into_iter().take(players - characters.len()) {
+                trace.push(TraceStep::CoopFiller(filler));
+                characters.push(filler);
+            }
+        }
+
+        if characters.len() < players {
+            trace.push(TraceStep::CoopNotEnoughCharacters(players));
+            return None;
+        }
+
+        let mut shared_targets: Vec<Target> = shared.iter().copied().collect();
+        shared_targets.sort_by_key(|targ| *targ as u32);
+        trace.push(TraceStep::CoopShared {
+            sharing: self.coop_targets,
+            targets: shared_targets,
+        });
+
+        let targets = self.roll_targets(&shared, &pool, rng, trace)?;
+        Some(CoopPick {
+            characters,
+            targets,
+        })
+    }
+
+    fn roll_chance<R: Rng + ?Sized>(
+        &self,
+        target: Target,
+        chance: f32,
+        targets: &mut HashSet<Target>,
+        rng: &mut R,
+        trace: &mut Trace,
+    ) {
+        let roll = rng.gen::<f32>();
+        let passed = roll <= chance;
+        trace.push(TraceStep::ChanceCheck {
+            target,
+            chance,
+            roll,
+            passed,
+        });
+
+        if passed {
+            targets.insert(target);
+        }
+    }
+
+    fn is_achievement_unlockable(&self, achievement: Achievement) -> bool {
+        self.is_dependency_val_unlockable(&DependencyValue::Achievement(achievement))
+    }
+
     pub fn is_target_significant(&self, targ: &Target) -> bool {
         use Target::*;
-        use crate::randomizer::dependency::{Polaroid, Negative, ItLives, Mom as MomDep};
+        use crate::randomizer::dependency::Mom as MomDep;
         match targ {
             BlueBaby | Lamb | MegaSatan | Delirium | Beast | Mother | UltraGreed | BossRush
-            | Hush => true,
-            Isaac if self.is_dependency_val_unlockable(&DependencyValue::Polaroid(Polaroid)) => true,
-            Satan if self.is_dependency_val_unlockable(&DependencyValue::Negative(Negative)) => true,
-            Heart if self.is_dependency_val_unlockable(&DependencyValue::ItLives(ItLives)) => true,
+            | Hush | Greedier => true,
+            Isaac if self.is_achievement_unlockable(Achievement::Polaroid) => true,
+            Satan if self.is_achievement_unlockable(Achievement::Negative) => true,
+            Heart if self.is_achievement_unlockable(Achievement::ItLives) => true,
             Mom if self.is_dependency_val_unlockable(&DependencyValue::Mom(MomDep)) => true,
             _ => false,
         }
@@ -657,37 +1449,138 @@ impl Unlocks {
 
 }
 
-impl Into<Savefile> for &Unlocks {
-    fn into(self) -> Savefile {
+// a hard mark is also a normal one
+fn with_normal_marks(marks: HashSet<(Target, Difficulty)>) -> HashSet<(Target, Difficulty)> {
+    let normal: Vec<(Target, Difficulty)> = marks
+        .iter()
+        .map(|(targ, _)| (*targ, Difficulty::Normal))
+        .collect();
+
+    marks.into_iter().chain(normal).collect()
+}
+
+impl From<&Unlocks> for Savefile {
+    fn from(val: &Unlocks) -> Self {
         Savefile::new(
             General::new(
-                self.unlocked_chars
+                val.unlocked_chars
                     .iter()
                     .map(|ch| -> String { format!("{}", ch) })
                     .collect(),
-                self.unlocked_targets
+                val.unlocked_targets
                     .iter()
                     .map(|targ| -> String { format!("{}", targ) })
                     .collect(),
-                self.is_mantle_unlocked,
-                self.is_it_lives_unlocked,
-                self.is_polaroid_unlocked,
-                self.is_negative_unlocked,
-                self.boss_rush_chance,
-                self.hush_chance,
-                self.roll_boss_rush_on_alt,
+                val.achievements
+                    .iter()
+                    .map(|achievement| -> String { format!("{}", achievement) })
+                    .collect(),
+                val.completed_challenges
+                    .iter()
+                    .map(|challenge| -> String { format!("{}", challenge) })
+                    .collect(),
+                val.completed_donations
+                    .iter()
+                    .map(|donation| -> String { format!("{}", donation) })
+                    .collect(),
+                val.boss_rush_chance,
+                val.hush_chance,
+                val.roll_boss_rush_on_alt,
+                format!("{}", val.weighting),
+                val.manual_weights
+                    .iter()
+                    .map(|(ch, weight)| -> (String, f32) { (format!("{}", ch), *weight) })
+                    .collect(),
+                val.no_repeat_char_within,
+                val.no_repeat_pair,
+                val.max_rerolls,
+                format!("{}", val.reroll_period),
+                val.ruleset.path().cloned().unwrap_or_default(),
+                val.prefer_hard,
+                format!("{}", val.coop_targets),
+                val.imply_marks,
             ),
-            HashMap::from_iter(self.marks.iter().map(|(ch, targs)| -> (String, Marks) {
+            HashMap::from_iter(val.marks.iter().map(|(ch, targs)| -> (String, Marks) {
+                let on_difficulty = |difficulty: Difficulty| -> Vec<String> {
+                    targs
+                        .iter()
+                        .filter(|(_, diff)| *diff == difficulty)
+                        .map(|(targ, _)| -> String { format!("{}", targ) })
+                        .collect()
+                };
+
                 (
                     format!("{}", ch),
-                    Marks::new(
-                        targs
+                    Marks::new(on_difficulty(Difficulty::Normal), on_difficulty(Difficulty::Hard)),
+                )
+            })),
+            val.history
+                .iter()
+                .map(|record| -> HistoryEntry {
+                    HistoryEntry::new(
+                        record.timestamp(),
+                        format!("{}", record.character()),
+                        record
+                            .targets()
                             .iter()
                             .map(|targ| -> String { format!("{}", targ) })
                             .collect(),
-                    ),
-                )
-            })),
+                        format!("{}", record.outcome()),
+                    )
+                })
+                .collect(),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocks_with_everyone() -> Unlocks {
+        let mut unlocks = Unlocks::default();
+        unlocks
+            .add_unlocked_chars(Character::iter().filter(|ch| !ch.is_tainted()).collect())
+            .add_unlocked_targets(Target::iter().filter(|targ| *targ != Target::Home).collect());
+        unlocks
+    }
+
+    #[test]
+    fn seeded_picks_are_reproducible() {
+        let unlocks = unlocks_with_everyone();
+
+        for seed in 0..32 {
+            // every pick builds its pools anew, with a different hash order
+            assert_eq!(
+                unlocks.get_seeded_random_pick(seed),
+                unlocks.get_seeded_random_pick(seed)
+            );
+            let coop_pick = |seed| {
+                let pick = unlocks.get_seeded_coop_pick(2, seed).unwrap();
+                (pick.characters().to_vec(), pick.targets().clone())
+            };
+            assert_eq!(coop_pick(seed), coop_pick(seed));
+        }
+
+        let picks: HashSet<Character> = (0..32)
+            .filter_map(|seed| unlocks.get_seeded_random_pick(seed))
+            .map(|(ch, _)| ch)
+            .collect();
+        assert!(picks.len() > 1);
+    }
+
+    // a shared seed has to give the same pick with any build of srati, this changes only when
+    // the roll itself does
+    #[test]
+    fn seeded_picks_stay_the_same_between_builds() {
+        let unlocks = unlocks_with_everyone();
+
+        assert_eq!(
+            unlocks.get_seeded_random_pick(42),
+            Some((
+                Character::Judas,
+                HashSet::from([Target::Satan, Target::Hush, Target::BossRush])
+            ))
+        );
+    }
+}
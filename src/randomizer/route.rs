@@ -0,0 +1,311 @@
+use crate::randomizer::targets::Target;
+use std::collections::HashSet;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Floor {
+    Basement,
+    Caves,
+    Depths,
+    Womb,
+    BlueWomb,
+    Cathedral,
+    Sheol,
+    Chest,
+    DarkRoom,
+    Void,
+    Downpour,
+    Mines,
+    Mausoleum,
+    Corpse,
+    Ascent,
+    Home,
+    GreedMode,
+    GreedierMode,
+}
+
+impl fmt::Display for Floor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Floor::*;
+        match self {
+            Basement => write!(f, "Basement"),
+            Caves => write!(f, "Caves"),
+            Depths => write!(f, "Depths"),
+            Womb => write!(f, "Womb"),
+            BlueWomb => write!(f, "Blue Womb"),
+            Cathedral => write!(f, "Cathedral"),
+            Sheol => write!(f, "Sheol"),
+            Chest => write!(f, "The Chest"),
+            DarkRoom => write!(f, "Dark Room"),
+            Void => write!(f, "The Void"),
+            Downpour => write!(f, "Downpour/Dross"),
+            Mines => write!(f, "Mines/Ashpit"),
+            Mausoleum => write!(f, "Mausoleum/Gehenna"),
+            Corpse => write!(f, "Corpse"),
+            Ascent => write!(f, "The Ascent"),
+            Home => write!(f, "Home"),
+            GreedMode => write!(f, "Greed Mode"),
+            GreedierMode => write!(f, "Greedier Mode"),
+        }
+    }
+}
+
+// items a route can't be finished without
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub enum Item {
+    Polaroid,
+    Negative,
+    KeyPiece1,
+    KeyPiece2,
+    KnifePiece1,
+    KnifePiece2,
+    RedKey,
+}
+
+impl Item {
+    pub fn found_at(&self) -> &'static str {
+        use Item::*;
+        match self {
+            Polaroid | Negative => "pick it out of Mom's drops on Depths II",
+            KeyPiece1 | KeyPiece2 => "given by the angels of angel rooms",
+            KnifePiece1 => "through the mirror on Downpour/Dross II, as a ghost",
+            KnifePiece2 => "at the end of the mineshaft chase on Mines/Ashpit II",
+            RedKey => "or a Cracked Key, to open the closet in Isaac's bedroom",
+        }
+    }
+}
+
+impl fmt::Display for Item {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Item::*;
+        match self {
+            Polaroid => write!(f, "The Polaroid"),
+            Negative => write!(f, "The Negative"),
+            KeyPiece1 => write!(f, "Key Piece 1"),
+            KeyPiece2 => write!(f, "Key Piece 2"),
+            KnifePiece1 => write!(f, "Knife Piece 1"),
+            KnifePiece2 => write!(f, "Knife Piece 2"),
+            RedKey => write!(f, "Red Key"),
+        }
+    }
+}
+
+// a floor of the route with the bosses to beat on it, in the order of their precedence
+#[derive(Clone, Debug)]
+pub struct Stop {
+    floor: Floor,
+    bosses: Vec<Target>,
+    // what to do on the floor that isn't beating its bosses, like how to get to the next floor
+    notes: Vec<&'static str>,
+}
+
+impl Stop {
+    pub fn floor(&self) -> Floor {
+        self.floor
+    }
+
+    pub fn bosses(&self) -> &Vec<Target> {
+        &self.bosses
+    }
+
+    pub fn notes(&self) -> &Vec<&'static str> {
+        &self.notes
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Route {
+    stops: Vec<Stop>,
+    items: Vec<Item>,
+    time_limits: Vec<(Target, &'static str)>,
+}
+
+impl Route {
+    // the floors a run for `targets` goes through, assuming the targets fit in one run like the
+    // ones a roll gives
+    pub fn plan(targets: &HashSet<Target>) -> Self {
+        use Target::*;
+
+        let has = |targ: Target| targets.contains(&targ);
+        let mut route = Route::default();
+
+        if has(Greedier) || has(UltraGreed) {
+            let floor = if has(Greedier) {
+                Floor::GreedierMode
+            } else {
+                Floor::GreedMode
+            };
+            route.visit(floor, targets.iter().copied().collect());
+            return route;
+        }
+
+        let to_chest = has(Isaac) || has(BlueBaby);
+        let to_dark_room = has(Satan) || has(Lamb);
+        // Mega Satan is behind the golden door of either of them, the chest is the default
+        let mega_satan_floor = if to_dark_room && !to_chest {
+            Floor::DarkRoom
+        } else {
+            Floor::Chest
+        };
+        let to_chest = to_chest || (has(MegaSatan) && mega_satan_floor == Floor::Chest);
+        let to_dark_room = to_dark_room || (has(MegaSatan) && mega_satan_floor == Floor::DarkRoom);
+        let to_blue_womb = has(Hush) || has(Delirium);
+        let to_womb = has(Heart) || to_blue_womb || to_chest || to_dark_room;
+
+        let mom_floor = if has(Mother) || has(Beast) || has(Home) {
+            route.visit(Floor::Basement, vec![]);
+            route.visit(Floor::Downpour, vec![]);
+            route.visit(Floor::Mines, vec![]);
+            route.visit(Floor::Mausoleum, vec![Mom]);
+            if has(Mother) {
+                route.visit(Floor::Corpse, vec![Mother]);
+                route.items.extend([Item::KnifePiece1, Item::KnifePiece2]);
+            }
+            if has(Beast) || has(Home) {
+                route.visit(Floor::Ascent, vec![]);
+                route.visit(
+                    Floor::Home,
+                    [Home, Beast].into_iter().filter(|t| has(*t)).collect(),
+                );
+            }
+            if has(Home) {
+                route.items.push(Item::RedKey);
+            }
+            Floor::Mausoleum
+        } else {
+            route.visit(Floor::Basement, vec![]);
+            route.visit(Floor::Caves, vec![]);
+            route.visit(Floor::Depths, vec![Mom]);
+            if to_womb {
+                route.visit(Floor::Womb, vec![Heart]);
+            }
+            if to_blue_womb {
+                route.visit(Floor::BlueWomb, vec![Hush]);
+                route
+                    .time_limits
+                    .push((Hush, "beat Mom's Heart before 30:00"));
+            }
+            if to_chest {
+                route.visit(Floor::Cathedral, vec![Isaac]);
+                let bosses = [BlueBaby, MegaSatan]
+                    .into_iter()
+                    .filter(|t| has(*t))
+                    .collect();
+                route.visit_last_floor(Floor::Chest, bosses, Item::Polaroid);
+            }
+            if to_dark_room {
+                route.visit(Floor::Sheol, vec![Satan]);
+                let bosses = [Lamb, MegaSatan].into_iter().filter(|t| has(*t)).collect();
+                route.visit_last_floor(Floor::DarkRoom, bosses, Item::Negative);
+            }
+            if has(Delirium) {
+                route.visit(Floor::Void, vec![Delirium]);
+            }
+            if has(MegaSatan) {
+                route.items.extend([Item::KeyPiece1, Item::KeyPiece2]);
+            }
+            Floor::Depths
+        };
+
+        if has(BossRush) {
+            if let Some(stop) = route.stops.iter_mut().find(|stop| stop.floor == mom_floor) {
+                stop.bosses.push(BossRush);
+            }
+            route.time_limits.push((BossRush, "beat Mom before 20:00"));
+        }
+
+        for i in 0..route.stops.len() {
+            let next = route.stops.get(i + 1).map(|stop| stop.floor);
+            let stop = &mut route.stops[i];
+            stop.bosses.sort_by_key(|targ| targ.precedence());
+            if stop.bosses.contains(&MegaSatan) {
+                stop.notes
+                    .push("Mega Satan is behind the golden door of the starting room");
+            }
+            if let Some(exit) = next.and_then(|next| exit_to(stop.floor, next)) {
+                stop.notes.push(exit);
+            }
+        }
+
+        route
+    }
+
+    // the chest and the dark room are only worth going to for their bosses, with the item that
+    // opens them
+    fn visit_last_floor(&mut self, floor: Floor, bosses: Vec<Target>, item: Item) {
+        if !bosses.is_empty() {
+            self.visit(floor, bosses);
+            self.items.push(item);
+        }
+    }
+
+    fn visit(&mut self, floor: Floor, bosses: Vec<Target>) {
+        self.stops.push(Stop {
+            floor,
+            bosses,
+            notes: Vec::new(),
+        });
+    }
+
+    pub fn stops(&self) -> &Vec<Stop> {
+        &self.stops
+    }
+
+    pub fn items(&self) -> &Vec<Item> {
+        &self.items
+    }
+
+    pub fn time_limits(&self) -> &Vec<(Target, &'static str)> {
+        &self.time_limits
+    }
+}
+
+// only the exits that are a choice, the other floors lead straight to the next one
+fn exit_to(floor: Floor, next: Floor) -> Option<&'static str> {
+    use Floor::*;
+    match (floor, next) {
+        (Basement, Downpour) => Some("take the secret exit to Downpour/Dross"),
+        (Womb, BlueWomb) => Some("take the Blue Womb door that opens after Mom's Heart"),
+        (Womb | BlueWomb, Cathedral) => Some("take the light beam up to the Cathedral"),
+        (Womb | BlueWomb, Sheol) => Some("take the trapdoor down to Sheol"),
+        (Cathedral, Chest) => Some("open the chest with The Polaroid"),
+        (Sheol, DarkRoom) => Some("go down the trapdoor with The Negative"),
+        (BlueWomb | Chest | DarkRoom, Void) => Some("take the Void portal"),
+        (Mausoleum, Corpse) => Some("open the strange door with the full knife"),
+        (Mausoleum, Ascent) => Some("pick up Dad's Note to start The Ascent"),
+        _ => None,
+    }
+}
+
+impl fmt::Display for Route {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "route:")?;
+        for (i, stop) in self.stops.iter().enumerate() {
+            write!(f, "{:>3}. {}", i + 1, stop.floor)?;
+            if !stop.bosses.is_empty() {
+                let bosses: Vec<String> = stop.bosses.iter().map(|boss| boss.to_string()).collect();
+                write!(f, ": {}", bosses.join(", "))?;
+            }
+            if !stop.notes.is_empty() {
+                write!(f, " -- {}", stop.notes.join(", then "))?;
+            }
+            writeln!(f)?;
+        }
+
+        if !self.items.is_empty() {
+            writeln!(f, "items:")?;
+            for item in &self.items {
+                writeln!(f, "     {} ({})", item, item.found_at())?;
+            }
+        }
+
+        if !self.time_limits.is_empty() {
+            writeln!(f, "time limits:")?;
+            for (targ, limit) in &self.time_limits {
+                writeln!(f, "     {}: {}", targ, limit)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
+use crate::randomizer::targets::Target;
+use std::collections::{HashMap, HashSet};
+use strum::IntoEnumIterator;
+
+// unlock rules the pool is resolved with, nodes without a rule depend on nothing
+#[derive(Clone, Debug)]
+pub struct Ruleset {
+    rules: HashMap<DependencyValue, Dependency>,
+    // what the dependency fallback tries to unlock when no unfinished character is left
+    unlockables: Vec<DependencyValue>,
+    // file the ruleset was loaded from, none for the built-in rules
+    path: Option<String>,
+}
+
+impl Default for Ruleset {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl Ruleset {
+    pub fn new(
+        rules: HashMap<DependencyValue, Dependency>,
+        unlockables: Vec<DependencyValue>,
+        path: Option<String>,
+    ) -> Self {
+        Self {
+            rules,
+            unlockables,
+            path,
+        }
+    }
+
+    pub fn builtin() -> Self {
+        let rules = DependencyValue::all()
+            .into_iter()
+            .map(|val| {
+                let dep = val.depends_on();
+                (val, dep)
+            })
+            .filter(|(_, dep)| !matches!(dep, Dependency::None))
+            .collect();
+
+        let mut unlockables = vec![
+            DependencyValue::Character(Character::Judas),
+            DependencyValue::Character(Character::BlueBaby),
+            DependencyValue::Character(Character::Keeper),
+            DependencyValue::Character(Character::Bethany),
+            DependencyValue::Character(Character::Apollyon),
+            DependencyValue::Achievement(Achievement::HolyMantle),
+            DependencyValue::Target(Target::BlueBaby),
+            DependencyValue::Target(Target::Lamb),
+            DependencyValue::Target(Target::MegaSatan),
+            DependencyValue::Target(Target::Delirium),
+            DependencyValue::Target(Target::Beast),
+            DependencyValue::Target(Target::Mother),
+            DependencyValue::Target(Target::Hush),
+            DependencyValue::Target(Target::Greedier),
+        ];
+        unlockables.extend(
+            Character::iter()
+                .filter(|ch| ch.is_tainted())
+                .map(DependencyValue::Character),
+        );
+
+        Self::new(rules, unlockables, None)
+    }
+
+    pub fn depends_on(&self, val: &DependencyValue) -> Dependency {
+        self.rules.get(val).cloned().unwrap_or(Dependency::None)
+    }
+
+    pub fn rules(&self) -> &HashMap<DependencyValue, Dependency> {
+        &self.rules
+    }
+
+    pub fn unlockables(&self) -> &Vec<DependencyValue> {
+        &self.unlockables
+    }
+
+    pub fn path(&self) -> Option<&String> {
+        self.path.as_ref()
+    }
+
+    // a chain of nodes that ends up depending on its own start, if there is one
+    pub fn find_cycle(&self) -> Option<Vec<DependencyValue>> {
+        let mut done = HashSet::new();
+
+        for start in DependencyValue::all() {
+            let mut chain = Vec::new();
+            if let Some(cycle) = self.find_cycle_from(&start, &mut chain, &mut done) {
+                return Some(cycle);
+            }
+        }
+
+        None
+    }
+
+    fn find_cycle_from(
+        &self,
+        val: &DependencyValue,
+        chain: &mut Vec<DependencyValue>,
+        done: &mut HashSet<DependencyValue>,
+    ) -> Option<Vec<DependencyValue>> {
+        if let Some(pos) = chain.iter().position(|chained| chained == val) {
+            let mut cycle = chain[pos..].to_vec();
+            cycle.push(val.clone());
+            return Some(cycle);
+        }
+        if done.contains(val) {
+            return None;
+        }
+
+        chain.push(val.clone());
+        let deps = match self.depends_on(val) {
+            Dependency::None => Vec::new(),
+            Dependency::Singular(dep_val) => vec![dep_val],
+            Dependency::Sum(dep_vals) | Dependency::Product(dep_vals) => dep_vals,
+        };
+        for dep_val in deps {
+            if let Some(cycle) = self.find_cycle_from(&dep_val, chain, done) {
+                return Some(cycle);
+            }
+        }
+        chain.pop();
+
+        done.insert(val.clone());
+        None
+    }
+}
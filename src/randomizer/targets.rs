@@ -1,7 +1,5 @@
-use crate::randomizer::characters::Character;
-use crate::randomizer::dependency::{
-    Dependency, DependencyValue, HasDependency, ItLives, Mantle, Negative, Polaroid,
-};
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::dependency::{Dependency, DependencyValue, HasDependency};
 use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::fmt;
@@ -27,6 +25,10 @@ pub enum Target {
     Heart,
 
     Mom,
+
+    Greedier,
+    // the closet at Home, opening it with the Red Key unlocks the character's tainted version
+    Home,
 }
 
 impl Target {
@@ -56,6 +58,9 @@ impl fmt::Display for Target {
             Heart => write!(f, "Mom's Heart"),
 
             Mom => write!(f, "Mom"),
+
+            Greedier => write!(f, "Ultra Greedier"),
+            Home => write!(f, "Home"),
         }
     }
 }
@@ -66,39 +71,84 @@ impl HasDependency for Target {
         use Dependency::*;
         use Target::*;
         match self {
-            BlueBaby => Singular(DependencyValue::Polaroid(Polaroid)),
-            Lamb => Singular(DependencyValue::Negative(Negative)),
+            BlueBaby => Singular(DependencyValue::Achievement(Achievement::Polaroid)),
+            Lamb => Singular(DependencyValue::Achievement(Achievement::Negative)),
             MegaSatan => Sum(vec![
                 DependencyValue::Target(Lamb),
                 DependencyValue::Target(BlueBaby),
             ]),
-            Delirium => Singular(DependencyValue::Target(Hush)),
-            Beast => Singular(DependencyValue::Target(Mother)),
+            Delirium => Singular(DependencyValue::Achievement(Achievement::TheVoid)),
+            Beast => Singular(DependencyValue::Achievement(Achievement::AStrangeDoor)),
             Mother => Product(vec![
-                DependencyValue::Target(Hush),
-                DependencyValue::Mantle(Mantle),
+                DependencyValue::Achievement(Achievement::ASecretExit),
+                DependencyValue::Achievement(Achievement::HolyMantle),
             ]),
             UltraGreed => None,
             BossRush => None,
-            Hush => Singular(DependencyValue::Character(Character::BlueBaby)),
+            Hush => Singular(DependencyValue::Achievement(Achievement::BlueWomb)),
 
-            Satan => Singular(DependencyValue::ItLives(ItLives)),
-            Isaac => Singular(DependencyValue::ItLives(ItLives)),
+            Satan => Singular(DependencyValue::Achievement(Achievement::ItLives)),
+            Isaac => Singular(DependencyValue::Achievement(Achievement::ItLives)),
             Heart => Singular(DependencyValue::Mom(DepMom)),
 
             Mom => None,
+
+            Greedier => Singular(DependencyValue::Target(UltraGreed)),
+            Home => Singular(DependencyValue::Target(Beast)),
         }
     }
 }
 
 impl Target {
     pub fn is_significant(&self) -> bool {
+        use Target::*;
+        matches!(
+            self,
+            BlueBaby
+                | Lamb
+                | MegaSatan
+                | Delirium
+                | Beast
+                | Mother
+                | UltraGreed
+                | BossRush
+                | Hush
+                | Greedier
+        )
+    }
+
+    // greed mode runs are separate from the normal floors, nothing else can be added to them
+    pub fn is_greed_mode(&self) -> bool {
+        matches!(self, Target::UltraGreed | Target::Greedier)
+    }
+
+    // targets that are beaten on the way to this one in the same run
+    pub fn passes_through(&self) -> HashSet<Target> {
         use Target::*;
         match self {
-            BlueBaby | Lamb | MegaSatan | Delirium | Beast | Mother | UltraGreed | BossRush
-            | Hush => true,
-            _ => false,
+            BlueBaby => HashSet::from([Isaac]),
+            Lamb => HashSet::from([Satan]),
+            MegaSatan | Isaac | Satan | Hush => HashSet::from([Heart]),
+            Delirium => HashSet::from([Hush]),
+            Heart | BossRush | Mother | Beast | Home => HashSet::from([Mom]),
+            Greedier => HashSet::from([UltraGreed]),
+            UltraGreed | Mom => HashSet::new(),
+        }
+    }
+
+    pub fn with_passed_through(targets: &HashSet<Target>) -> HashSet<Target> {
+        let mut all = targets.clone();
+        let mut to_visit: Vec<Target> = targets.iter().copied().collect();
+
+        while let Some(targ) = to_visit.pop() {
+            for passed in targ.passes_through() {
+                if all.insert(passed) {
+                    to_visit.push(passed);
+                }
+            }
         }
+
+        all
     }
 
     pub fn precedence(&self) -> u8 {
@@ -109,6 +159,7 @@ impl Target {
             BlueBaby => 5,
             Lamb => 4,
             Beast => 4,
+            Home => 4,
             Mother => 4,
             Satan => 4,
             Isaac => 4,
@@ -118,6 +169,7 @@ impl Target {
             Mom => 0,
 
             UltraGreed => 0,
+            Greedier => 0,
         }
     }
 }
@@ -153,6 +205,8 @@ impl FromStr for Target {
             "Isaac" => Ok(Isaac),
             "Mom's Heart" => Ok(Heart),
             "Mom" => Ok(Mom),
+            "Ultra Greedier" => Ok(Greedier),
+            "Home" => Ok(Home),
             _ => Err("Could not convert string to Target"),
         }
     }
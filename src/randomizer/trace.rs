@@ -0,0 +1,196 @@
+use crate::randomizer::characters::Character;
+use crate::randomizer::coop::CoopTargets;
+use crate::randomizer::targets::Target;
+use crate::randomizer::weighting::Weighting;
+use std::fmt;
+
+// a decision made while rolling, in the order it was made
+#[derive(Clone, Debug)]
+pub enum TraceStep {
+    EverythingCompleted,
+    UnfinishedPool(Vec<Character>),
+    DependencyFallback,
+    Unlockable(String),
+    Locked(String),
+    TargetAdded {
+        target: Target,
+        characters: Vec<Character>,
+        unlocks: String,
+    },
+    Excluded {
+        characters: Vec<Character>,
+        targets: Vec<Target>,
+    },
+    // characters with marks only done on normal, the only ones kept with prefer_hard
+    HardLeftovers(Vec<Character>),
+    HistoryRulesApplied(Vec<Character>),
+    HistoryRulesDropped,
+    CharacterPicked {
+        character: Character,
+        candidates: usize,
+        weighting: Weighting,
+    },
+    TargetPicked {
+        target: Target,
+        candidates: Vec<Target>,
+    },
+    GreedModeAlone(Target),
+    MegaSatanAdded(Target),
+    DeliriumWithHush,
+    Forced(Target),
+    SkippedOnAltPath(Target),
+    ChanceCheck {
+        target: Target,
+        chance: f32,
+        roll: f32,
+        passed: bool,
+    },
+    CoopFiller(Character),
+    CoopNotEnoughCharacters(usize),
+    CoopShared {
+        sharing: CoopTargets,
+        targets: Vec<Target>,
+    },
+    NothingRolled,
+}
+
+impl fmt::Display for TraceStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use TraceStep::*;
+        match self {
+            EverythingCompleted => {
+                write!(f, "every mark is completed, rolling from everything")
+            }
+            UnfinishedPool(chars) => write!(
+                f,
+                "rolling from unfinished characters with targets left: {}",
+                join(chars)
+            ),
+            DependencyFallback => write!(
+                f,
+                "no unfinished character has a target left, rolling for what unlocks more"
+            ),
+            Unlockable(dep) => write!(f, "{} can be unlocked now", dep),
+            Locked(dep) => write!(f, "{} is locked, looking at what it depends on", dep),
+            TargetAdded {
+                target,
+                characters,
+                unlocks,
+            } => {
+                if characters.len() > 3 {
+                    write!(
+                        f,
+                        "added {} for {} characters (unlocks {})",
+                        target,
+                        characters.len(),
+                        unlocks
+                    )
+                } else {
+                    write!(
+                        f,
+                        "added {} for {} (unlocks {})",
+                        target,
+                        join(characters),
+                        unlocks
+                    )
+                }
+            }
+            Excluded {
+                characters,
+                targets,
+            } => write!(
+                f,
+                "excluded characters: [{}], targets: [{}]",
+                join(characters),
+                join(targets)
+            ),
+            HardLeftovers(chars) => write!(
+                f,
+                "preferring marks only done on Normal, to do on Hard: {}",
+                join(chars)
+            ),
+            HistoryRulesApplied(chars) => {
+                write!(f, "history rules removed: [{}]", join(chars))
+            }
+            HistoryRulesDropped => write!(
+                f,
+                "history rules would leave nothing to roll, ignoring them"
+            ),
+            CharacterPicked {
+                character,
+                candidates,
+                weighting,
+            } => write!(
+                f,
+                "picked {} out of {} characters ({} weighting)",
+                character, candidates, weighting
+            ),
+            TargetPicked { target, candidates } => {
+                write!(f, "picked {} out of [{}]", target, join(candidates))
+            }
+            GreedModeAlone(targ) => {
+                write!(f, "{} is a separate run, nothing is added to it", targ)
+            }
+            MegaSatanAdded(targ) => write!(f, "Mega Satan is added to {}", targ),
+            DeliriumWithHush => write!(
+                f,
+                "only Delirium is left, rolling it with Hush on the way"
+            ),
+            Forced(targ) => write!(f, "{} is forced since one target is left", targ),
+            SkippedOnAltPath(targ) => write!(f, "{} is skipped on the alt path", targ),
+            ChanceCheck {
+                target,
+                chance,
+                roll,
+                passed,
+            } => write!(
+                f,
+                "{} chance check: rolled {:.2} against {:.2}, {}",
+                target,
+                roll,
+                chance,
+                if *passed { "passed" } else { "failed" }
+            ),
+            CoopFiller(ch) => write!(
+                f,
+                "nothing left to roll for more characters, {} joins to fill a spot",
+                ch
+            ),
+            CoopNotEnoughCharacters(players) => write!(
+                f,
+                "not enough characters share targets for {} players",
+                players
+            ),
+            CoopShared { sharing, targets } => write!(
+                f,
+                "targets useful for {} of the characters: [{}]",
+                sharing,
+                join(targets)
+            ),
+            NothingRolled => write!(f, "no targets were rolled"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+}
+
+impl Trace {
+    pub fn push(&mut self, step: TraceStep) {
+        self.steps.push(step);
+    }
+
+    pub fn steps(&self) -> &Vec<TraceStep> {
+        &self.steps
+    }
+}
+
+fn join(items: &[impl fmt::Display]) -> String {
+    items
+        .iter()
+        .map(|item| item.to_string())
+        .collect::<Vec<String>>()
+        .join(", ")
+}
@@ -0,0 +1,62 @@
+use rand::distributions::WeightedIndex;
+use rand::prelude::Distribution;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Weighting {
+    #[default]
+    Uniform,
+    Remaining,
+    Inverse,
+    Manual,
+}
+
+impl Weighting {
+    // picks an item proportionally to its weight, falls back to a uniform pick when no item has
+    // a positive weight
+    pub fn choose<T: Copy, R: Rng + ?Sized>(items: &[T], weights: &[f32], rng: &mut R) -> Option<T> {
+        match WeightedIndex::new(weights) {
+            Ok(dist) => items.get(dist.sample(rng)).copied(),
+            Err(_) => items.choose(rng).copied(),
+        }
+    }
+
+    // weight of something that has `remaining` marks left to do
+    pub fn remaining_weight(&self, remaining: usize) -> f32 {
+        match self {
+            Weighting::Uniform | Weighting::Manual => 1.0,
+            Weighting::Remaining => remaining as f32,
+            Weighting::Inverse => 1.0 / remaining.max(1) as f32,
+        }
+    }
+}
+
+impl fmt::Display for Weighting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        use Weighting::*;
+        match self {
+            Uniform => write!(f, "uniform"),
+            Remaining => write!(f, "remaining"),
+            Inverse => write!(f, "inverse"),
+            Manual => write!(f, "manual"),
+        }
+    }
+}
+
+impl FromStr for Weighting {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Weighting::*;
+        match s {
+            "uniform" => Ok(Uniform),
+            "remaining" => Ok(Remaining),
+            "inverse" => Ok(Inverse),
+            "manual" => Ok(Manual),
+            _ => Err("Could not convert string to Weighting"),
+        }
+    }
+}
@@ -1 +1,2 @@
+pub mod ruleset;
 pub mod savefile;
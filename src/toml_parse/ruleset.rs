@@ -0,0 +1,183 @@
+use crate::randomizer::dependency::{Dependency, DependencyValue};
+use crate::randomizer::ruleset::Ruleset;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::str::FromStr;
+
+#[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
+pub enum Error {
+    SerializeError(toml::ser::Error),
+    DeserializeError(toml::de::Error),
+    JsonError(serde_json::Error),
+    IoError(std::io::Error),
+    UnknownNameError(String),
+    DuplicateRuleError(String),
+    // a rule with both any_of and all_of
+    AmbiguousRuleError(String),
+    CycleError(Vec<String>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SerializeError(e) => write!(f, "couldn't write the ruleset: {}", e),
+            Error::DeserializeError(e) => write!(f, "couldn't read the ruleset: {}", e),
+            Error::JsonError(e) => write!(f, "couldn't read the ruleset: {}", e),
+            Error::IoError(e) => write!(f, "couldn't open the ruleset: {}", e),
+            Error::UnknownNameError(name) => write!(f, "unknown node \"{}\"", name),
+            Error::DuplicateRuleError(name) => write!(f, "\"{}\" has more than one rule", name),
+            Error::AmbiguousRuleError(name) => {
+                write!(f, "the rule for \"{}\" has both any_of and all_of", name)
+            }
+            Error::CycleError(chain) => {
+                write!(f, "dependency cycle: {}", chain.join(" -> "))
+            }
+        }
+    }
+}
+
+impl From<toml::ser::Error> for Error {
+    fn from(e: toml::ser::Error) -> Self {
+        Error::SerializeError(e)
+    }
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(e: toml::de::Error) -> Self {
+        Error::DeserializeError(e)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Error::JsonError(e)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(e: std::io::Error) -> Self {
+        Error::IoError(e)
+    }
+}
+
+// nodes are named like `character Isaac`, `target Mom's Heart`, achievements (`Holy Mantle`,
+// `The Void`) and `Mom's Heart` (beating Mom)
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RulesetFile {
+    unlockables: Vec<String>,
+    #[serde(default)]
+    rules: Vec<Rule>,
+}
+
+// a node that depends on one value is written as a single entry of all_of
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Rule {
+    node: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    any_of: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    all_of: Vec<String>,
+}
+
+impl RulesetFile {
+    // json for files ending with .json, toml otherwise
+    pub fn read_from_file(path: &str) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)?;
+
+        if path.ends_with(".json") {
+            Ok(serde_json::from_str(&contents)?)
+        } else {
+            Ok(toml::from_str(&contents)?)
+        }
+    }
+
+    pub fn to_toml_string(&self) -> Result<String, Error> {
+        Ok(toml::to_string(&self)?)
+    }
+
+    pub fn to_json_string(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string_pretty(&self)?)
+    }
+}
+
+pub fn read_ruleset(path: &str) -> Result<Ruleset, Error> {
+    let file = RulesetFile::read_from_file(path)?;
+    // saved absolute, so the savefile still finds it when srati runs from another directory
+    let path = fs::canonicalize(path)?;
+    into_ruleset(file, Some(path.to_string_lossy().to_string()))
+}
+
+fn parse_node(name: &str) -> Result<DependencyValue, Error> {
+    DependencyValue::from_str(name).map_err(|_| Error::UnknownNameError(name.to_string()))
+}
+
+fn parse_nodes(names: &[String]) -> Result<Vec<DependencyValue>, Error> {
+    names.iter().map(|name| parse_node(name)).collect()
+}
+
+fn into_ruleset(file: RulesetFile, path: Option<String>) -> Result<Ruleset, Error> {
+    let mut rules = HashMap::new();
+
+    for rule in file.rules {
+        let node = parse_node(&rule.node)?;
+        let any_of = parse_nodes(&rule.any_of)?;
+        let mut all_of = parse_nodes(&rule.all_of)?;
+
+        let dep = match (any_of.is_empty(), all_of.len()) {
+            (false, 0) => Dependency::Sum(any_of),
+            (false, _) => return Err(Error::AmbiguousRuleError(rule.node)),
+            (true, 0) => Dependency::None,
+            (true, 1) => Dependency::Singular(all_of.remove(0)),
+            (true, _) => Dependency::Product(all_of),
+        };
+
+        if rules.insert(node, dep).is_some() {
+            return Err(Error::DuplicateRuleError(rule.node));
+        }
+    }
+
+    let unlockables = parse_nodes(&file.unlockables)?;
+    let ruleset = Ruleset::new(rules, unlockables, path);
+
+    match ruleset.find_cycle() {
+        Some(cycle) => Err(Error::CycleError(
+            cycle.iter().map(|val| val.to_string()).collect(),
+        )),
+        None => Ok(ruleset),
+    }
+}
+
+impl From<&Ruleset> for RulesetFile {
+    fn from(val: &Ruleset) -> Self {
+        let to_names = |vals: &Vec<DependencyValue>| -> Vec<String> {
+            vals.iter().map(|val| val.to_string()).collect()
+        };
+
+        // rules are written in graph order so exported files stay diffable
+        let rules = DependencyValue::all()
+            .iter()
+            .filter_map(|node| {
+                let (any_of, all_of) = match val.rules().get(node)? {
+                    Dependency::None => return None,
+                    Dependency::Singular(dep_val) => (Vec::new(), vec![dep_val.to_string()]),
+                    Dependency::Sum(dep_vals) => (to_names(dep_vals), Vec::new()),
+                    Dependency::Product(dep_vals) => (Vec::new(), to_names(dep_vals)),
+                };
+
+                Some(Rule {
+                    node: node.to_string(),
+                    any_of,
+                    all_of,
+                })
+            })
+            .collect();
+
+        RulesetFile {
+            unlockables: to_names(val.unlockables()),
+            rules,
+        }
+    }
+}
@@ -1,18 +1,55 @@
+use crate::randomizer::achievements::Achievement;
+use crate::randomizer::challenges::Challenge;
 use crate::randomizer::characters::Character;
+use crate::randomizer::coop::CoopTargets;
+use crate::randomizer::difficulty::Difficulty;
+use crate::randomizer::donations::Donation;
+use crate::randomizer::history::{Outcome, RerollPeriod, RollRecord};
 use crate::randomizer::pool::Unlocks;
 use crate::randomizer::targets::Target;
+use crate::randomizer::weighting::Weighting;
+use crate::toml_parse::ruleset;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::fmt;
 use std::fs;
 use std::str::FromStr;
+use strum::IntoEnumIterator;
 
 #[derive(Debug)]
+#[allow(clippy::enum_variant_names)]
 pub enum Error {
     SerializeError(toml::ser::Error),
     DeserializeError(toml::de::Error),
     ParseError,
     IoError(std::io::Error),
+    RulesetError(ruleset::Error),
+    // written by a newer version of srati
+    UnsupportedVersionError(i64),
+    // every problem that keeps the savefile from loading
+    ValidationError(Vec<Problem>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SerializeError(e) => write!(f, "couldn't write the savefile: {}", e),
+            Error::DeserializeError(e) => write!(f, "couldn't read the savefile: {}", e),
+            Error::ParseError => write!(f, "the savefile has an invalid value"),
+            Error::IoError(e) => write!(f, "{}", e),
+            Error::RulesetError(e) => write!(f, "couldn't load the ruleset: {}", e),
+            Error::UnsupportedVersionError(version) => write!(
+                f,
+                "savefile version {} is newer than the supported version {}",
+                version, SAVEFILE_VERSION
+            ),
+            Error::ValidationError(problems) => {
+                let lines: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+                write!(f, "{}", lines.join("\n"))
+            }
+        }
+    }
 }
 
 impl From<toml::ser::Error> for Error {
@@ -39,17 +76,41 @@ impl From<std::io::Error> for Error {
     }
 }
 
+impl From<ruleset::Error> for Error {
+    fn from(e: ruleset::Error) -> Self {
+        Error::RulesetError(e)
+    }
+}
+
+// version written by this build, savefiles from before versioning have no version key and are 0
+pub const SAVEFILE_VERSION: i64 = 1;
+
+// MIGRATIONS[v] upgrades a savefile from version v to v + 1
+const MIGRATIONS: [fn(&mut toml::Table); SAVEFILE_VERSION as usize] = [migrate_v0_to_v1];
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Savefile {
+    #[serde(default)]
+    version: i64,
+    #[serde(default)]
     general_config: General,
+    #[serde(default)]
     marks: HashMap<String, Marks>,
+    #[serde(default)]
+    history: Vec<HistoryEntry>,
 }
 
 impl Savefile {
-    pub fn new(general_config: General, marks: HashMap<String, Marks>) -> Self {
+    pub fn new(
+        general_config: General,
+        marks: HashMap<String, Marks>,
+        history: Vec<HistoryEntry>,
+    ) -> Self {
         Self {
+            version: SAVEFILE_VERSION,
             general_config,
             marks,
+            history,
         }
     }
 
@@ -62,63 +123,574 @@ impl Savefile {
         }
     }
 
+    // savefiles of older versions are migrated and written back, the old file is kept next to it
     pub fn read_from_file(path: String) -> Result<Self, Error> {
-        let toml_str = fs::read_to_string(path)?;
+        let (table, version) = read_table(&path)?;
+        if version == SAVEFILE_VERSION {
+            return Ok(toml::Value::Table(table).try_into()?);
+        }
 
-        match toml::from_str(&toml_str) {
-            Err(e) => Err(Error::DeserializeError(e)),
-            Ok(s) => Ok(s),
+        fs::copy(&path, format!("{}.v{}.bak", path, version))?;
+        let savefile = migrate(table, version)?;
+        savefile.write_to_file(path)?;
+        Ok(savefile)
+    }
+
+    // reads the savefile without changing the file, an older version is only migrated in memory
+    // and reported as a problem next to the ones validate finds
+    pub fn check_file(path: String) -> Result<(Self, Vec<Problem>), Error> {
+        let (table, version) = read_table(&path)?;
+        let savefile = migrate(table, version)?;
+
+        let mut problems = Vec::new();
+        if version != SAVEFILE_VERSION {
+            problems.push(Problem::warning(
+                "version".to_string(),
+                format!(
+                    "version {} needs migrating to {}, the next command loading it does that",
+                    version, SAVEFILE_VERSION
+                ),
+            ));
         }
+        problems.extend(savefile.validate());
+
+        Ok((savefile, problems))
     }
 }
 
+// the savefile's table with its version, 0 for savefiles from before versioning
+fn read_table(path: &str) -> Result<(toml::Table, i64), Error> {
+    let toml_str = fs::read_to_string(path)?;
+    let table: toml::Table = toml::from_str(&toml_str)?;
+
+    let version = match table.get("version") {
+        None => 0,
+        Some(toml::Value::Integer(v)) => *v,
+        Some(_) => return Err(Error::ParseError),
+    };
+    if !(0..=SAVEFILE_VERSION).contains(&version) {
+        return Err(Error::UnsupportedVersionError(version));
+    }
+
+    Ok((table, version))
+}
+
+fn migrate(mut table: toml::Table, version: i64) -> Result<Savefile, Error> {
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut table);
+    }
+    table.insert(
+        "version".to_string(),
+        toml::Value::Integer(SAVEFILE_VERSION),
+    );
+
+    Ok(toml::Value::Table(table).try_into()?)
+}
+
+// the four achievement booleans become the achievements list, the floors' achievements and Home
+// are added for what was reached through them, and the names of Bethany, Tainted Bethany and
+// Tainted Azazel are spelled correctly
+fn migrate_v0_to_v1(table: &mut toml::Table) {
+    if let Some(toml::Value::Table(general)) = table.get_mut("general_config") {
+        let old_achievements = [
+            ("is_mantle_unlocked", Achievement::HolyMantle),
+            ("is_it_lives_unlocked", Achievement::ItLives),
+            ("is_polaroid_unlocked", Achievement::Polaroid),
+            ("is_negative_unlocked", Achievement::Negative),
+        ];
+        for (key, achievement) in old_achievements {
+            if let Some(toml::Value::Boolean(true)) = general.remove(key) {
+                add_achievement(general, achievement);
+            }
+        }
+
+        // the achievements opening the alt floors weren't tracked, an unlocked target on such a
+        // floor means they were unlocked
+        let floor_achievements = [
+            (Target::Hush, Achievement::BlueWomb),
+            (Target::Delirium, Achievement::TheVoid),
+            (Target::Mother, Achievement::ASecretExit),
+            (Target::Beast, Achievement::AStrangeDoor),
+        ];
+        for (target, achievement) in floor_achievements {
+            let is_unlocked = match general.get("unlocked_targets") {
+                Some(toml::Value::Array(targets)) => targets
+                    .iter()
+                    .any(|targ| targ.as_str() == Some(target.to_string().as_str())),
+                _ => false,
+            };
+            if is_unlocked {
+                add_achievement(general, achievement);
+            }
+        }
+
+        if let Some(toml::Value::Array(chars)) = general.get_mut("unlocked_chars") {
+            chars.iter_mut().for_each(rename_character);
+        }
+
+        // tainted characters are found in the closet at Home, which wasn't a target before
+        let has_tainted = match general.get("unlocked_chars") {
+            Some(toml::Value::Array(chars)) => chars.iter().any(|ch| {
+                ch.as_str()
+                    .and_then(|name| Character::from_str(name).ok())
+                    .is_some_and(|ch| ch.is_tainted())
+            }),
+            _ => false,
+        };
+        if has_tainted {
+            let targets = general
+                .entry("unlocked_targets")
+                .or_insert_with(|| toml::Value::Array(Vec::new()));
+            if let toml::Value::Array(targets) = targets {
+                let home = toml::Value::String(Target::Home.to_string());
+                if !targets.contains(&home) {
+                    targets.push(home);
+                }
+            }
+        }
+        if let Some(toml::Value::Table(weights)) = general.get_mut("manual_weights") {
+            rename_character_key(weights);
+        }
+    }
+
+    if let Some(toml::Value::Table(marks)) = table.get_mut("marks") {
+        rename_character_key(marks);
+    }
+
+    if let Some(toml::Value::Array(history)) = table.get_mut("history") {
+        for entry in history {
+            if let Some(ch) = entry.get_mut("character") {
+                rename_character(ch);
+            }
+        }
+    }
+}
+
+fn add_achievement(general: &mut toml::Table, achievement: Achievement) {
+    let achievements = general
+        .entry("achievements")
+        .or_insert_with(|| toml::Value::Array(Vec::new()));
+    if let toml::Value::Array(achievements) = achievements {
+        let name = toml::Value::String(achievement.to_string());
+        if !achievements.contains(&name) {
+            achievements.push(name);
+        }
+    }
+}
+
+// names v0 spelled wrong, with the character they are spelled correctly as now
+const OLD_NAMES: [(&str, Character); 3] = [
+    ("Behtany", Character::Bethany),
+    ("Tainted Behtany", Character::TaintedBethany),
+    ("Tainted Azazael", Character::TaintedAzazel),
+];
+
+fn rename_character(ch: &mut toml::Value) {
+    for (old_name, renamed) in OLD_NAMES {
+        if ch.as_str() == Some(old_name) {
+            *ch = toml::Value::String(renamed.to_string());
+        }
+    }
+}
+
+fn rename_character_key(table: &mut toml::Table) {
+    for (old_name, renamed) in OLD_NAMES {
+        if let Some(val) = table.remove(old_name) {
+            table.insert(renamed.to_string(), val);
+        }
+    }
+}
+
+// missing fields get the values of a new savefile
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 pub struct General {
     unlocked_chars: Vec<String>,
     unlocked_targets: Vec<String>,
-    is_mantle_unlocked: bool,
-    is_it_lives_unlocked: bool,
-    is_polaroid_unlocked: bool,
-    is_negative_unlocked: bool,
+    achievements: Vec<String>,
+    completed_challenges: Vec<String>,
+    completed_donations: Vec<String>,
     boss_rush_chance: f32,
     hush_chance: f32,
     roll_boss_rush_on_alt: bool,
+    weighting: String,
+    manual_weights: HashMap<String, f32>,
+    no_repeat_char_within: usize,
+    no_repeat_pair: bool,
+    max_rerolls: usize,
+    reroll_period: String,
+    // path to a ruleset file, empty for the built-in rules
+    ruleset: String,
+    prefer_hard: bool,
+    coop_targets: String,
+    imply_marks: bool,
 }
 
 impl General {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         unlocked_chars: Vec<String>,
         unlocked_targets: Vec<String>,
-        is_mantle_unlocked: bool,
-        is_it_lives_unlocked: bool,
-        is_polaroid_unlocked: bool,
-        is_negative_unlocked: bool,
+        achievements: Vec<String>,
+        completed_challenges: Vec<String>,
+        completed_donations: Vec<String>,
         boss_rush_chance: f32,
         hush_chance: f32,
         roll_boss_rush_on_alt: bool,
+        weighting: String,
+        manual_weights: HashMap<String, f32>,
+        no_repeat_char_within: usize,
+        no_repeat_pair: bool,
+        max_rerolls: usize,
+        reroll_period: String,
+        ruleset: String,
+        prefer_hard: bool,
+        coop_targets: String,
+        imply_marks: bool,
     ) -> Self {
         Self {
             unlocked_chars,
             unlocked_targets,
-            is_mantle_unlocked,
-            is_it_lives_unlocked,
-            is_polaroid_unlocked,
-            is_negative_unlocked,
+            achievements,
+            completed_challenges,
+            completed_donations,
             boss_rush_chance,
             hush_chance,
             roll_boss_rush_on_alt,
+            weighting,
+            manual_weights,
+            no_repeat_char_within,
+            no_repeat_pair,
+            max_rerolls,
+            reroll_period,
+            ruleset,
+            prefer_hard,
+            coop_targets,
+            imply_marks,
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+impl Default for General {
+    fn default() -> Self {
+        Savefile::from(&Unlocks::default()).general_config
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Marks {
     completed: Vec<String>,
+    // savefiles from before difficulties only have normal marks
+    completed_hard: Vec<String>,
 }
 
 impl Marks {
-    pub fn new(completed: Vec<String>) -> Self {
-        Self { completed }
+    pub fn new(completed: Vec<String>, completed_hard: Vec<String>) -> Self {
+        Self {
+            completed,
+            completed_hard,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct HistoryEntry {
+    timestamp: u64,
+    character: String,
+    targets: Vec<String>,
+    outcome: String,
+}
+
+impl HistoryEntry {
+    pub fn new(timestamp: u64, character: String, targets: Vec<String>, outcome: String) -> Self {
+        Self {
+            timestamp,
+            character,
+            targets,
+            outcome,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Severity {
+    // the savefile can't be loaded
+    Error,
+    // the savefile loads, but probably not the way it was meant to
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+// a problem found in a savefile, with the TOML key it was found at
+#[derive(Clone, Debug)]
+pub struct Problem {
+    severity: Severity,
+    location: String,
+    message: String,
+}
+
+impl Problem {
+    fn error(location: String, message: String) -> Self {
+        Self {
+            severity: Severity::Error,
+            location,
+            message,
+        }
+    }
+
+    fn warning(location: String, message: String) -> Self {
+        Self {
+            severity: Severity::Warning,
+            location,
+            message,
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn location(&self) -> &str {
+        &self.location
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}: {}", self.severity, self.location, self.message)
+    }
+}
+
+impl Savefile {
+    // every problem in the savefile, errors and warnings, in the order of the file
+    pub fn validate(&self) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let general = &self.general_config;
+        let at = |key: &str| format!("general_config.{}", key);
+
+        let chars: HashSet<Character> = general
+            .unlocked_chars
+            .iter()
+            .enumerate()
+            .filter_map(|(i, ch_str)| {
+                check_name(
+                    ch_str,
+                    at(&format!("unlocked_chars[{}]", i)),
+                    "character",
+                    &mut problems,
+                )
+            })
+            .collect();
+        let targs: HashSet<Target> = general
+            .unlocked_targets
+            .iter()
+            .enumerate()
+            .filter_map(|(i, targ_str)| {
+                check_name(
+                    targ_str,
+                    at(&format!("unlocked_targets[{}]", i)),
+                    "target",
+                    &mut problems,
+                )
+            })
+            .collect();
+        for (i, achievement_str) in general.achievements.iter().enumerate() {
+            let location = at(&format!("achievements[{}]", i));
+            check_name::<Achievement>(achievement_str, location, "achievement", &mut problems);
+        }
+        for (i, challenge_str) in general.completed_challenges.iter().enumerate() {
+            let location = at(&format!("completed_challenges[{}]", i));
+            check_name::<Challenge>(challenge_str, location, "challenge", &mut problems);
+        }
+        for (i, donation_str) in general.completed_donations.iter().enumerate() {
+            let location = at(&format!("completed_donations[{}]", i));
+            check_name::<Donation>(donation_str, location, "donation machine", &mut problems);
+        }
+
+        for (key, chance) in [
+            ("boss_rush_chance", general.boss_rush_chance),
+            ("hush_chance", general.hush_chance),
+        ] {
+            if !(0.0..=1.0).contains(&chance) {
+                problems.push(Problem::warning(
+                    at(key),
+                    format!("chance {} is outside of 0-1", chance),
+                ));
+            }
+        }
+
+        check_setting::<Weighting>(
+            &general.weighting,
+            at("weighting"),
+            "weighting",
+            &mut problems,
+        );
+        check_setting::<RerollPeriod>(
+            &general.reroll_period,
+            at("reroll_period"),
+            "reroll period",
+            &mut problems,
+        );
+        check_setting::<CoopTargets>(
+            &general.coop_targets,
+            at("coop_targets"),
+            "co-op targets",
+            &mut problems,
+        );
+
+        for ch_str in sorted_keys(&general.manual_weights) {
+            let location = at(&format!("manual_weights.{}", toml_key(ch_str)));
+            check_name::<Character>(ch_str, location, "character", &mut problems);
+        }
+
+        for ch_str in sorted_keys(&self.marks) {
+            let location = format!("marks.{}", toml_key(ch_str));
+            if let Some(ch) =
+                check_name::<Character>(ch_str, location.clone(), "character", &mut problems)
+            {
+                if !chars.contains(&ch) {
+                    problems.push(Problem::warning(
+                        location.clone(),
+                        format!("marks for {}, who is not in unlocked_chars", ch),
+                    ));
+                }
+            }
+
+            let marks = &self.marks[ch_str];
+            for (key, targ_strs) in [
+                ("completed", &marks.completed),
+                ("completed_hard", &marks.completed_hard),
+            ] {
+                for (i, targ_str) in targ_strs.iter().enumerate() {
+                    let location = format!("{}.{}[{}]", location, key, i);
+                    if let Some(targ) =
+                        check_name::<Target>(targ_str, location.clone(), "target", &mut problems)
+                    {
+                        if !targs.contains(&targ) {
+                            problems.push(Problem::warning(
+                                location,
+                                format!("mark for {}, which is not in unlocked_targets", targ),
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        for (i, entry) in self.history.iter().enumerate() {
+            let location = format!("history[{}]", i);
+            check_name::<Character>(
+                &entry.character,
+                format!("{}.character", location),
+                "character",
+                &mut problems,
+            );
+            for (j, targ_str) in entry.targets.iter().enumerate() {
+                let location = format!("{}.targets[{}]", location, j);
+                check_name::<Target>(targ_str, location, "target", &mut problems);
+            }
+            if Outcome::from_str(entry.outcome.as_str()).is_err() {
+                problems.push(Problem::error(
+                    format!("{}.outcome", location),
+                    format!("unknown outcome \"{}\"", entry.outcome),
+                ));
+            }
+        }
+
+        problems
+    }
+}
+
+fn check_name<T: FromStr + IntoEnumIterator + fmt::Display>(
+    name: &str,
+    location: String,
+    kind: &str,
+    problems: &mut Vec<Problem>,
+) -> Option<T> {
+    if let Ok(val) = T::from_str(name) {
+        return Some(val);
+    }
+
+    let mut message = format!("unknown {} \"{}\"", kind, name);
+    if let Some(suggestion) = closest_name(name, T::iter().map(|val| val.to_string())) {
+        message.push_str(format!(", did you mean \"{}\"?", suggestion).as_str());
+    }
+    problems.push(Problem::error(location, message));
+    None
+}
+
+// settings left empty get their default value
+fn check_setting<T: FromStr>(
+    value: &str,
+    location: String,
+    kind: &str,
+    problems: &mut Vec<Problem>,
+) {
+    if !value.is_empty() && T::from_str(value).is_err() {
+        problems.push(Problem::error(
+            location,
+            format!("unknown {} \"{}\"", kind, value),
+        ));
+    }
+}
+
+// the name with the fewest edits away, if it's close enough to be a typo
+fn closest_name(name: &str, candidates: impl Iterator<Item = String>) -> Option<String> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 4).max(2);
+
+    candidates
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = prev[j] + usize::from(ca != *cb);
+            curr[j + 1] = substitution.min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+
+    prev[b.len()]
+}
+
+fn sorted_keys<V>(map: &HashMap<String, V>) -> Vec<&String> {
+    let mut keys: Vec<&String> = map.keys().collect();
+    keys.sort();
+    keys
+}
+
+// keys with spaces or symbols have to be quoted in TOML
+fn toml_key(key: &str) -> String {
+    if !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        key.to_string()
+    } else {
+        format!("\"{}\"", key)
     }
 }
 
@@ -126,6 +698,15 @@ impl TryInto<Unlocks> for Savefile {
     type Error = Error;
 
     fn try_into(self) -> Result<Unlocks, Self::Error> {
+        let errors: Vec<Problem> = self
+            .validate()
+            .into_iter()
+            .filter(|problem| problem.severity() == Severity::Error)
+            .collect();
+        if !errors.is_empty() {
+            return Err(Error::ValidationError(errors));
+        }
+
         let mut unl = Unlocks::default();
 
         let mut chars = HashSet::new();
@@ -147,19 +728,234 @@ impl TryInto<Unlocks> for Savefile {
             let mut marks_set = HashSet::new();
             for targ_str in marks.completed {
                 let targ = Target::from_str(targ_str.as_str())?;
-                marks_set.insert(targ);
+                marks_set.insert((targ, Difficulty::Normal));
+            }
+            for targ_str in marks.completed_hard {
+                let targ = Target::from_str(targ_str.as_str())?;
+                marks_set.insert((targ, Difficulty::Hard));
             }
-            unl.set_marks(ch, marks_set);
+            unl.set_saved_marks(ch, marks_set);
         }
 
-        unl.set_mantle_unlocked(self.general_config.is_mantle_unlocked)
-            .set_it_lives_unlocked(self.general_config.is_it_lives_unlocked)
-            .set_polaroid_unlocked(self.general_config.is_polaroid_unlocked)
-            .set_negative_unlocked(self.general_config.is_negative_unlocked)
-            .set_boss_rush_chance(self.general_config.boss_rush_chance)
+        for achievement_str in self.general_config.achievements {
+            let achievement = Achievement::from_str(achievement_str.as_str())?;
+            unl.set_achievement_unlocked(achievement, true);
+        }
+
+        for challenge_str in self.general_config.completed_challenges {
+            let challenge = Challenge::from_str(challenge_str.as_str())?;
+            unl.set_challenge_completed(challenge, true);
+        }
+
+        for donation_str in self.general_config.completed_donations {
+            let donation = Donation::from_str(donation_str.as_str())?;
+            unl.set_donation_completed(donation, true);
+        }
+
+        unl.set_boss_rush_chance(self.general_config.boss_rush_chance)
             .set_hush_chance(self.general_config.hush_chance)
             .set_roll_boss_rush_on_alt(self.general_config.roll_boss_rush_on_alt);
 
+        if !self.general_config.weighting.is_empty() {
+            unl.set_weighting(Weighting::from_str(self.general_config.weighting.as_str())?);
+        }
+
+        for (ch_str, weight) in self.general_config.manual_weights {
+            let ch = Character::from_str(ch_str.as_str())?;
+            unl.set_manual_weight(ch, weight);
+        }
+
+        unl.set_no_repeat_char_within(self.general_config.no_repeat_char_within)
+            .set_no_repeat_pair(self.general_config.no_repeat_pair)
+            .set_max_rerolls(self.general_config.max_rerolls)
+            .set_prefer_hard(self.general_config.prefer_hard)
+            .set_imply_marks(self.general_config.imply_marks);
+
+        if !self.general_config.reroll_period.is_empty() {
+            unl.set_reroll_period(RerollPeriod::from_str(
+                self.general_config.reroll_period.as_str(),
+            )?);
+        }
+
+        if !self.general_config.coop_targets.is_empty() {
+            unl.set_coop_targets(CoopTargets::from_str(
+                self.general_config.coop_targets.as_str(),
+            )?);
+        }
+
+        if !self.general_config.ruleset.is_empty() {
+            unl.set_ruleset(ruleset::read_ruleset(self.general_config.ruleset.as_str())?);
+        }
+
+        for entry in self.history {
+            let ch = Character::from_str(entry.character.as_str())?;
+            let mut targets = HashSet::new();
+            for targ_str in entry.targets {
+                targets.insert(Target::from_str(targ_str.as_str())?);
+            }
+            let outcome = Outcome::from_str(entry.outcome.as_str())?;
+            unl.push_history(RollRecord::new(entry.timestamp, ch, targets, outcome));
+        }
+
         Ok(unl)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const V0_SAVEFILE: &str = r#"
+[general_config]
+unlocked_chars = ["Isaac", "Lazarus", "Behtany", "Tainted Behtany", "Azazel", "Tainted Azazael"]
+unlocked_targets = ["Mom", "Mom's Heart", "Hush"]
+is_mantle_unlocked = true
+
+[general_config.manual_weights]
+"Tainted Azazael" = 2.0
+
+[marks.Behtany]
+completed = ["Mom"]
+
+[[history]]
+timestamp = 1
+character = "Tainted Behtany"
+targets = ["Mom"]
+outcome = "done"
+"#;
+
+    // a directory of its own for every test, the tests run in parallel
+    fn temp_path(name: &str) -> String {
+        let dir = std::env::temp_dir().join(format!("srati-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("sf.toml").to_string_lossy().to_string()
+    }
+
+    fn remove_temp_path(path: &str) {
+        if let Some(dir) = std::path::Path::new(path).parent() {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+
+    #[test]
+    fn migrates_v0_with_renames_and_backup() {
+        let path = temp_path("migration");
+        fs::write(&path, V0_SAVEFILE).unwrap();
+
+        let savefile = Savefile::read_from_file(path.clone()).unwrap();
+
+        assert_eq!(savefile.version, SAVEFILE_VERSION);
+        let chars = &savefile.general_config.unlocked_chars;
+        for name in ["Bethany", "Tainted Bethany", "Tainted Azazel"] {
+            assert!(chars.contains(&name.to_string()), "{} is missing", name);
+        }
+        for (old_name, _) in OLD_NAMES {
+            assert!(!chars.contains(&old_name.to_string()), "{} is left", old_name);
+        }
+        assert!(savefile.marks.contains_key("Bethany"));
+        assert!(savefile.general_config.manual_weights.contains_key("Tainted Azazel"));
+        assert_eq!(savefile.history[0].character, "Tainted Bethany");
+
+        let general = &savefile.general_config;
+        assert!(general.achievements.contains(&"Holy Mantle".to_string()));
+        assert!(general.achievements.contains(&"Blue Womb".to_string()));
+        assert!(general.unlocked_targets.contains(&"Home".to_string()));
+        assert!(savefile.validate().is_empty());
+
+        assert_eq!(fs::read_to_string(format!("{}.v0.bak", path)).unwrap(), V0_SAVEFILE);
+        let written: toml::Table = toml::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(written["version"].as_integer(), Some(SAVEFILE_VERSION));
+
+        // the migrated file loads as it is
+        let savefile = Savefile::read_from_file(path.clone()).unwrap();
+        assert!(TryInto::<Unlocks>::try_into(savefile).is_ok());
+        remove_temp_path(&path);
+    }
+
+    #[test]
+    fn rejects_newer_versions() {
+        let path = temp_path("newer");
+        fs::write(&path, format!("version = {}", SAVEFILE_VERSION + 1)).unwrap();
+
+        let result = Savefile::read_from_file(path.clone());
+        assert!(matches!(result, Err(Error::UnsupportedVersionError(_))));
+        remove_temp_path(&path);
+    }
+
+    #[test]
+    fn check_file_leaves_old_versions_alone() {
+        let path = temp_path("check");
+        fs::write(&path, V0_SAVEFILE).unwrap();
+
+        let (savefile, problems) = Savefile::check_file(path.clone()).unwrap();
+
+        assert_eq!(savefile.version, SAVEFILE_VERSION);
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].location(), "version");
+        assert_eq!(problems[0].severity(), Severity::Warning);
+        assert_eq!(fs::read_to_string(&path).unwrap(), V0_SAVEFILE);
+        assert!(!std::path::Path::new(&format!("{}.v0.bak", path)).exists());
+        remove_temp_path(&path);
+    }
+
+    fn problem_at<'a>(problems: &'a [Problem], location: &str) -> &'a Problem {
+        problems
+            .iter()
+            .find(|problem| problem.location() == location)
+            .unwrap_or_else(|| panic!("no problem at {}", location))
+    }
+
+    #[test]
+    fn validate_reports_problems_with_their_keys() {
+        let savefile: Savefile = toml::from_str(
+            r#"
+version = 1
+
+[general_config]
+unlocked_chars = ["Isaac", "Tainted Azazael"]
+unlocked_targets = ["Mom", "Huhs"]
+hush_chance = 1.5
+weighting = "sometimes"
+
+[marks.Cain]
+completed = ["Mom"]
+
+[marks.Isaac]
+completed = ["Satan", "Bestt"]
+"#,
+        )
+        .unwrap();
+
+        let problems = savefile.validate();
+
+        let problem = problem_at(&problems, "general_config.unlocked_chars[1]");
+        assert_eq!(problem.severity(), Severity::Error);
+        assert_eq!(
+            problem.message(),
+            "unknown character \"Tainted Azazael\", did you mean \"Tainted Azazel\"?"
+        );
+        let problem = problem_at(&problems, "general_config.unlocked_targets[1]");
+        assert!(problem.message().ends_with("did you mean \"Hush\"?"));
+        let problem = problem_at(&problems, "marks.Isaac.completed[1]");
+        assert!(problem.message().ends_with("did you mean \"Beast\"?"));
+
+        let problem = problem_at(&problems, "general_config.hush_chance");
+        assert_eq!(problem.severity(), Severity::Warning);
+        let problem = problem_at(&problems, "general_config.weighting");
+        assert_eq!(problem.severity(), Severity::Error);
+        let problem = problem_at(&problems, "marks.Cain");
+        assert_eq!(problem.severity(), Severity::Warning);
+        let problem = problem_at(&problems, "marks.Isaac.completed[0]");
+        assert_eq!(problem.severity(), Severity::Warning);
+        assert_eq!(problems.len(), 7);
+    }
+
+    #[test]
+    fn closest_name_skips_names_too_far_away() {
+        let names = || Character::iter().map(|ch| ch.to_string());
+
+        assert_eq!(closest_name("magdalen", names()), Some("Magdalene".to_string()));
+        assert_eq!(closest_name("jacob & esua", names()), Some("Jacob & Esau".to_string()));
+        assert_eq!(closest_name("Mr. Mega", names()), None);
+    }
+}